@@ -51,6 +51,7 @@ contract Setup is Script {
         // Mine hook address with correct permission flags via CREATE2
         uint160 flags = uint160(
             Hooks.BEFORE_ADD_LIQUIDITY_FLAG | Hooks.BEFORE_REMOVE_LIQUIDITY_FLAG | Hooks.AFTER_SWAP_FLAG
+                | Hooks.AFTER_DONATE_FLAG
         );
         (, bytes32 salt) = HookMiner.find(
             0x4e59b44847b379578588920cA78FbF26c0B4956C, // CREATE2 Deployer Proxy
@@ -185,6 +186,7 @@ contract DeployRangeVault is Script {
         // Mine hook address with correct permission flags via CREATE2
         uint160 flags = uint160(
             Hooks.BEFORE_ADD_LIQUIDITY_FLAG | Hooks.BEFORE_REMOVE_LIQUIDITY_FLAG | Hooks.AFTER_SWAP_FLAG
+                | Hooks.AFTER_DONATE_FLAG
         );
         (, bytes32 salt) = HookMiner.find(
             0x4e59b44847b379578588920cA78FbF26c0B4956C,
@@ -160,6 +160,7 @@ contract SetupArbSepolia is Script {
 
         uint160 flags = uint160(
             Hooks.BEFORE_ADD_LIQUIDITY_FLAG | Hooks.BEFORE_REMOVE_LIQUIDITY_FLAG | Hooks.AFTER_SWAP_FLAG
+                | Hooks.AFTER_DONATE_FLAG
         );
 
         (, bytes32 salt) = HookMiner.find(
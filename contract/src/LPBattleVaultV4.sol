@@ -291,7 +291,7 @@ contract LPBattleVaultV4 is IERC721Receiver, Pausable, ReentrancyGuard {
         // Register with hook
         if (address(battleHook) != address(0)) {
             battleHook.registerBattle(battleId, poolKey);
-            battleHook.lockPosition(poolKey, tickLower, tickUpper);
+            battleHook.lockPosition(poolKey, tickLower, tickUpper, tokenId);
         }
 
         emit BattleCreated(battleId, msg.sender, tokenId, duration, usdValue);
@@ -348,7 +348,7 @@ contract LPBattleVaultV4 is IERC721Receiver, Pausable, ReentrancyGuard {
 
         // Lock position with hook
         if (address(battleHook) != address(0)) {
-            battleHook.lockPosition(poolKey, tickLower, tickUpper);
+            battleHook.lockPosition(poolKey, tickLower, tickUpper, tokenId);
         }
 
         emit BattleJoined(battleId, msg.sender, tokenId, block.timestamp);
@@ -198,7 +198,7 @@ contract UniswapV4Adapter is IDEXAdapter, IERC721Receiver {
             PoolKey memory poolKey = cachedPoolKeys[tokenId];
             int24 tickLower = cachedTickLower[tokenId];
             int24 tickUpper = cachedTickUpper[tokenId];
-            battleHook.lockPosition(poolKey, tickLower, tickUpper);
+            battleHook.lockPosition(poolKey, tickLower, tickUpper, tokenId);
         }
     }
 
@@ -9,18 +9,31 @@ import {PoolId, PoolIdLibrary} from "v4-core/types/PoolId.sol";
 import {BalanceDelta} from "v4-core/types/BalanceDelta.sol";
 import {BeforeSwapDelta, BeforeSwapDeltaLibrary} from "v4-core/types/BeforeSwapDelta.sol";
 import {StateLibrary} from "v4-core/libraries/StateLibrary.sol";
+import {FullMath} from "v4-core/libraries/FullMath.sol";
+import {FixedPoint128} from "v4-core/libraries/FixedPoint128.sol";
 import {Currency} from "v4-core/types/Currency.sol";
 import {ModifyLiquidityParams, SwapParams} from "v4-core/types/PoolOperation.sol";
 
-import {IBattleVaultHook, PositionLockedInBattle, InvalidHookCaller} from "../interfaces/IShared.sol";
+import {
+    IBattleVaultHook,
+    PositionLockedInBattle,
+    InvalidHookCaller,
+    TooManyLockedRanges
+} from "../interfaces/IShared.sol";
 
 /// @title Battle Vault Hook for Uniswap V4
 /// @notice Integrates LP BattleVault with Uniswap V4 hook system
-/// @dev Monitors swaps to track in-range status of battling positions
+/// @dev Monitors swaps to track in-range status of battling positions, and records
+///      donations so fee battles can remove donated fees from a player's score
 contract BattleVaultHook is BaseHook, IBattleVaultHook {
     using PoolIdLibrary for PoolKey;
     using StateLibrary for IPoolManager;
 
+    // ============ Constants ============
+
+    /// @notice Most distinct ranges a pool can have locked at once, bounding the loop in `_afterDonate`
+    uint256 public constant MAX_LOCKED_RANGES = 32;
+
     // ============ State ============
 
     /// @notice The LP Battle Vault contract
@@ -35,6 +48,9 @@ contract BattleVaultHook is BaseHook, IBattleVaultHook {
     /// @notice Position lock status (poolId => tickLower => tickUpper => locked)
     mapping(PoolId => mapping(int24 => mapping(int24 => bool))) public positionLocked;
 
+    /// @notice Number of battle positions holding each range locked; the range unlocks at zero
+    mapping(PoolId => mapping(int24 => mapping(int24 => uint256))) public rangeLockCount;
+
     /// @notice Battle position data
     struct BattlePosition {
         uint256 battleId;
@@ -47,12 +63,30 @@ contract BattleVaultHook is BaseHook, IBattleVaultHook {
     /// @notice Mapping of pool ID to battle positions
     mapping(PoolId => BattlePosition[]) public battlePositions;
 
+    /// @notice Locked position range
+    struct TickRange {
+        int24 tickLower;
+        int24 tickUpper;
+    }
+
+    /// @notice Locked ranges per pool, credited with donations while in range
+    mapping(PoolId => TickRange[]) internal lockedRanges;
+
+    /// @notice Cumulative donated fee growth per unit of liquidity (X128) a range has accrued while
+    ///         locked (poolId => tickLower => tickUpper => [token0, token1]). Never reset; it wraps
+    ///         like the pool's own fee growth, and readers take the difference from a snapshot
+    mapping(PoolId => mapping(int24 => mapping(int24 => uint256[2]))) internal donationGrowthX128;
+
+    /// @notice Range donation growth when each position was locked (tokenId => [token0, token1])
+    mapping(uint256 => uint256[2]) internal donationGrowthAtLockX128;
+
     // ============ Events ============
 
     event BattleRegistered(uint256 indexed battleId, PoolId indexed poolId);
     event PositionLocked(PoolId indexed poolId, int24 tickLower, int24 tickUpper);
     event PositionUnlocked(PoolId indexed poolId, int24 tickLower, int24 tickUpper);
     event SwapProcessed(PoolId indexed poolId, int24 newTick);
+    event DonationRecorded(PoolId indexed poolId, uint256 amount0, uint256 amount1, uint128 liquidity);
 
     // ============ Constructor ============
 
@@ -73,7 +107,7 @@ contract BattleVaultHook is BaseHook, IBattleVaultHook {
             beforeSwap: false,
             afterSwap: true,               // Update in-range status after swaps
             beforeDonate: false,
-            afterDonate: false,
+            afterDonate: true,             // Record donated fee growth for locked ranges
             beforeSwapReturnDelta: false,
             afterSwapReturnDelta: false,
             afterAddLiquidityReturnDelta: false,
@@ -139,6 +173,45 @@ contract BattleVaultHook is BaseHook, IBattleVaultHook {
         return (this.afterSwap.selector, 0);
     }
 
+    /// @notice Called after every donation - record the fee growth it adds to locked ranges
+    /// @dev A donation is paid to the liquidity in range at the current tick, so each locked range
+    ///      in range now receives amount * 2^128 / liquidity per unit of its liquidity, exactly as
+    ///      the pool's fee growth does. Out-of-range positions receive nothing and are not charged.
+    ///      The loop is bounded by MAX_LOCKED_RANGES, and the accumulators wrap modulo 2^256.
+    function _afterDonate(
+        address,
+        PoolKey calldata key,
+        uint256 amount0,
+        uint256 amount1,
+        bytes calldata
+    ) internal override returns (bytes4) {
+        PoolId poolId = key.toId();
+        uint128 liquidity = poolManager.getLiquidity(poolId);
+        if (liquidity == 0) {
+            return this.afterDonate.selector;
+        }
+
+        uint256 growth0 = FullMath.mulDiv(amount0, FixedPoint128.Q128, liquidity);
+        uint256 growth1 = FullMath.mulDiv(amount1, FixedPoint128.Q128, liquidity);
+        (, int24 currentTick, , ) = poolManager.getSlot0(poolId);
+
+        TickRange[] storage ranges = lockedRanges[poolId];
+        for (uint256 i = 0; i < ranges.length; i++) {
+            TickRange memory range = ranges[i];
+            if (currentTick >= range.tickLower && currentTick < range.tickUpper) {
+                uint256[2] storage growth = donationGrowthX128[poolId][range.tickLower][range.tickUpper];
+                unchecked {
+                    growth[0] += growth0;
+                    growth[1] += growth1;
+                }
+            }
+        }
+
+        emit DonationRecorded(poolId, amount0, amount1, liquidity);
+
+        return this.afterDonate.selector;
+    }
+
     // ============ Battle Vault Functions ============
 
     /// @notice Register a battle with this hook
@@ -157,25 +230,39 @@ contract BattleVaultHook is BaseHook, IBattleVaultHook {
     }
 
     /// @notice Lock a position for a battle
+    /// @dev Snapshots the range's donation growth for the position, so a later battle on a range
+    ///      that is already locked does not inherit the donations made before it joined
     /// @param poolKey The pool key
     /// @param tickLower Lower tick of the position
     /// @param tickUpper Upper tick of the position
+    /// @param tokenId The position's token ID
     function lockPosition(
         PoolKey calldata poolKey,
         int24 tickLower,
-        int24 tickUpper
+        int24 tickUpper,
+        uint256 tokenId
     ) external {
         if (msg.sender != battleVault) {
             revert InvalidHookCaller();
         }
 
         PoolId poolId = poolKey.toId();
-        positionLocked[poolId][tickLower][tickUpper] = true;
+        if (rangeLockCount[poolId][tickLower][tickUpper]++ == 0) {
+            if (lockedRanges[poolId].length >= MAX_LOCKED_RANGES) {
+                revert TooManyLockedRanges();
+            }
+            positionLocked[poolId][tickLower][tickUpper] = true;
+            lockedRanges[poolId].push(TickRange(tickLower, tickUpper));
+        }
+
+        uint256[2] storage growth = donationGrowthX128[poolId][tickLower][tickUpper];
+        donationGrowthAtLockX128[tokenId] = [growth[0], growth[1]];
 
         emit PositionLocked(poolId, tickLower, tickUpper);
     }
 
     /// @notice Unlock a position after battle resolution
+    /// @dev The range stays locked while another battle position still holds it
     /// @param poolKey The pool key
     /// @param tickLower Lower tick of the position
     /// @param tickUpper Upper tick of the position
@@ -189,7 +276,22 @@ contract BattleVaultHook is BaseHook, IBattleVaultHook {
         }
 
         PoolId poolId = poolKey.toId();
-        positionLocked[poolId][tickLower][tickUpper] = false;
+        uint256 lockCount = rangeLockCount[poolId][tickLower][tickUpper];
+        if (lockCount > 1) {
+            rangeLockCount[poolId][tickLower][tickUpper] = lockCount - 1;
+        } else if (lockCount == 1) {
+            delete rangeLockCount[poolId][tickLower][tickUpper];
+            positionLocked[poolId][tickLower][tickUpper] = false;
+
+            TickRange[] storage ranges = lockedRanges[poolId];
+            for (uint256 i = 0; i < ranges.length; i++) {
+                if (ranges[i].tickLower == tickLower && ranges[i].tickUpper == tickUpper) {
+                    ranges[i] = ranges[ranges.length - 1];
+                    ranges.pop();
+                    break;
+                }
+            }
+        }
 
         emit PositionUnlocked(poolId, tickLower, tickUpper);
     }
@@ -249,6 +351,29 @@ contract BattleVaultHook is BaseHook, IBattleVaultHook {
         return positionLocked[poolId][tickLower][tickUpper];
     }
 
+    /// @notice Get the donated fee growth a position's range has accrued since the position was locked
+    /// @dev Pass these with the position's liquidity to BattleScoring's
+    ///      `calculate_donation_adjusted_fee_score` at resolution. The difference wraps like
+    ///      the accumulator it is taken from
+    /// @param poolKey The pool key
+    /// @param tickLower Lower tick
+    /// @param tickUpper Upper tick
+    /// @param tokenId The position's token ID
+    /// @return growth0X128 Donated token0 per unit of liquidity, Q128
+    /// @return growth1X128 Donated token1 per unit of liquidity, Q128
+    function getDonationGrowthInside(
+        PoolKey calldata poolKey,
+        int24 tickLower,
+        int24 tickUpper,
+        uint256 tokenId
+    ) external view returns (uint256 growth0X128, uint256 growth1X128) {
+        uint256[2] storage growth = donationGrowthX128[poolKey.toId()][tickLower][tickUpper];
+        uint256[2] storage atLock = donationGrowthAtLockX128[tokenId];
+        unchecked {
+            return (growth[0] - atLock[0], growth[1] - atLock[1]);
+        }
+    }
+
     /// @notice Get all active battles for a pool
     /// @param poolKey The pool key
     /// @return battleIds Array of active battle IDs
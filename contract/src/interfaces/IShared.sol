@@ -118,3 +118,4 @@ error BattleNotExpiredForEmergencyWithdrawal();
 error PositionLockedInBattle();
 error InvalidHookCaller();
 error HookNotAuthorized();
+error TooManyLockedRanges();
@@ -1118,7 +1118,7 @@ contract MockBattleVaultHook {
         registeredBattles[battleId] = true;
     }
 
-    function lockPosition(PoolKey calldata key, int24 tickLower, int24 tickUpper) external {
+    function lockPosition(PoolKey calldata key, int24 tickLower, int24 tickUpper, uint256) external {
         bytes32 poolId = PoolId.unwrap(key.toId());
         positionLocked[poolId][tickLower][tickUpper] = true;
     }
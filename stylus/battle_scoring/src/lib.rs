@@ -1,10 +1,22 @@
 #![cfg_attr(not(any(feature = "export-abi", test, not(target_arch = "wasm32"))), no_main)]
-#[cfg(any(target_arch = "wasm32", feature = "export-abi"))]
+#![recursion_limit = "256"]
 extern crate alloc;
 
 #[cfg(any(target_arch = "wasm32", feature = "export-abi"))]
 use stylus_sdk::prelude::*;
-use alloy_primitives::U256;
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), not(feature = "views")))]
+use alloy_primitives::{aliases::U8, Address};
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), not(feature = "views")))]
+use alloc::{string::String, vec::Vec};
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), not(feature = "views")))]
+use stylus_sdk::storage::StorageGuard;
+use alloy_primitives::{keccak256, U256, U512};
+use alloy_sol_types::sol;
+use fixed_math::tick_math::{
+    amounts_for_liquidity, fees_owed, quote_token0_in_token1, sqrt_ratio_at_tick, MAX_TICK, MIN_TICK,
+};
+use fixed_math::{mul_div_down, mul_div_up, MathError};
+use stylus_sdk::stylus_proc::SolidityError;
 
 // ============ Constants ============
 
@@ -15,26 +27,278 @@ const SCORE_DECIMALS: u64 = 1_000_000_000_000_000_000;
 const MAX_BPS: u64 = 10_000;
 
 /// Tick distance bonus: positions with tighter range get up to 20% bonus
-/// Threshold below which tick distance earns a bonus (100 ticks)
+/// Default threshold below which tick distance earns a bonus (100 ticks)
 const TIGHT_RANGE_THRESHOLD: u64 = 100;
 
-/// Maximum bonus for tight ranges (20% = 0.2 * 1e18)
+/// Default maximum bonus for tight ranges (20% = 0.2 * 1e18)
 const TIGHT_RANGE_BONUS: u64 = 200_000_000_000_000_000;
 
-/// DEX normalization weight basis points (10000 = 1.0x)
-/// Index 0 = UNISWAP_V4, Index 1 = CAMELOT_V3
-const DEX_WEIGHT_BPS: [u64; 2] = [10_000, 10_000];
+/// Widest tick distance the owner may use as a threshold (MAX_TICK - MIN_TICK)
+const MAX_RANGE_THRESHOLD: u64 = 1_774_544;
 
-#[cfg(any(target_arch = "wasm32", feature = "export-abi"))]
+/// Largest tightness bonus the owner may set (100% = 1e18)
+const MAX_RANGE_BONUS: u64 = SCORE_DECIMALS;
+
+/// Default DEX normalization weight in basis points (10000 = 1.0x).
+/// Used when seeding the built-in DEXes on initialization.
+pub const DEFAULT_DEX_WEIGHT_BPS: u64 = 10_000;
+
+/// Built-in DEX types, matching `IDEXAdapter.DEXType` on the Solidity side
+pub const DEX_UNISWAP_V4: u8 = 0;
+pub const DEX_CAMELOT_V3: u8 = 1;
+
+/// Lowest DEX weight the owner may set (5000 = 0.5x)
+const MIN_DEX_WEIGHT_BPS: u64 = 5_000;
+
+/// Highest DEX weight the owner may set (20000 = 2.0x)
+const MAX_DEX_WEIGHT_BPS: u64 = 20_000;
+
+/// Default relative tolerance under which two scores count as a draw (10 bps = 0.1%)
+pub const DEFAULT_DRAW_TOLERANCE_BPS: u64 = 10;
+
+/// Largest draw tolerance the owner may set (1000 bps = 10%)
+const MAX_DRAW_TOLERANCE_BPS: u64 = 1_000;
+
+/// Seconds in a 365-day year, used to annualize fee yields
+const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+/// Default share of the range score in a hybrid score (5000 bps = 50/50 blend)
+pub const DEFAULT_HYBRID_RANGE_WEIGHT_BPS: u64 = 5_000;
+
+/// Default fee score that maps to a full 1e18 in a hybrid score: a 100% yearly
+/// yield, i.e. 1e18 / 365 days
+pub const DEFAULT_HYBRID_FEE_CEILING: u64 = SCORE_DECIMALS / SECONDS_PER_YEAR;
+
+/// Default largest gap between tick checkpoints accepted when computing in-range time (1 hour)
+pub const DEFAULT_MAX_CHECKPOINT_GAP: u64 = 3_600;
+
+/// Default liquidity share that earns full density weight (1000 bps = 10% of active liquidity)
+pub const DEFAULT_LIQUIDITY_SHARE_CAP_BPS: u64 = 1_000;
+
+/// Decimals of USD values passed to and built by the scoring engine
+const USD_DECIMALS: u8 = 8;
+
+/// Largest token or price decimals accepted; keeps 10^(tokenDecimals + priceDecimals) within U256
+const MAX_TOKEN_DECIMALS: u8 = 38;
+
+/// Battle outcomes returned by `outcome`
+pub const OUTCOME_DRAW: u8 = 0;
+pub const OUTCOME_PLAYER_A: u8 = 1;
+pub const OUTCOME_PLAYER_B: u8 = 2;
+
+/// Tie-break rules returned by `break_tie`, in the order they are tried
+pub const TIEBREAK_TICK_WIDTH: u8 = 1;
+pub const TIEBREAK_USD_VALUE: u8 = 2;
+pub const TIEBREAK_JOIN_TIME: u8 = 3;
+pub const TIEBREAK_COIN_FLIP: u8 = 4;
+
+/// Battle types, matching `IBattleArena.BattleType` on the Solidity side
+pub const BATTLE_TYPE_RANGE: u8 = 0;
+pub const BATTLE_TYPE_FEE: u8 = 1;
+/// Reserved for a net-PnL battle type scored with `net_pnl_score`
+pub const BATTLE_TYPE_NET_PNL: u8 = 2;
+
+/// Player count bounds for `rank_players`
+pub const MIN_RANKED_PLAYERS: usize = 2;
+pub const MAX_RANKED_PLAYERS: usize = 8;
+
+// ============ Errors ============
+
+sol! {
+    #![sol(all_derives)]
+
+    /// In-range time is larger than the battle duration.
+    error InRangeExceedsTotal(uint256 inRangeTime, uint256 totalTime);
+    /// An intermediate value does not fit in 256 bits.
+    error Overflow();
+    /// Battle duration (or total time) is zero.
+    error ZeroDuration();
+    /// LP position value is zero, so a yield rate is undefined.
+    error ZeroLpValue();
+    /// The dex_type was never registered.
+    error UnknownDex(uint8 dexType);
+    /// The dex_type is registered but currently disabled.
+    error DexDisabled(uint8 dexType);
+    /// The dex_type is already registered.
+    error DexAlreadyRegistered(uint8 dexType);
+    /// The DEX was unregistered or disabled when the parameter set was published.
+    error DexNotInParamSet(uint8 dexType);
+    /// No parameter set has been published under this version.
+    error UnknownParamSet(uint256 version);
+    /// Caller is not the owner.
+    error NotOwner(address caller);
+    /// The owner cannot be the zero address.
+    error ZeroAddressOwner();
+    /// DEX weight is outside [MIN_DEX_WEIGHT_BPS, MAX_DEX_WEIGHT_BPS].
+    error InvalidDexWeight(uint256 weightBps);
+    /// Range threshold or maximum bonus is out of bounds.
+    error InvalidRangeParams(uint256 threshold, uint256 maxBonus);
+    /// Resolver bps limits do not satisfy min <= max <= 10000.
+    error InvalidResolverBpsLimits(uint256 minBps, uint256 maxBps);
+    /// Resolver bps is outside the limits of the parameter set.
+    error ResolverBpsOutsideLimits(uint256 resolverBps, uint256 minBps, uint256 maxBps);
+    /// Draw tolerance exceeds MAX_DRAW_TOLERANCE_BPS.
+    error InvalidDrawTolerance(uint256 toleranceBps);
+    /// Reward bps exceed the allowed combined maximum.
+    error RewardBpsOutOfRange(uint256 resolverBps, uint256 protocolBps, uint256 maxCombinedBps);
+    /// A ranked battle has fewer than MIN_RANKED_PLAYERS or more than MAX_RANKED_PLAYERS players.
+    error InvalidPlayerCount(uint256 count);
+    /// Payout curve is empty, longer than the player count, or does not sum to 10000 bps.
+    error InvalidPayoutCurve(uint256 length, uint256 totalBps);
+    /// Placements are not a valid ranking (1, 1, 3, ...).
+    error InvalidPlacements();
+    /// Hybrid range weight exceeds 10000 bps or the fee ceiling is zero.
+    error InvalidHybridParams(uint256 rangeWeightBps, uint256 feeCeiling);
+    /// Token or price decimals exceed MAX_TOKEN_DECIMALS.
+    error InvalidDecimals(uint8 decimals);
+    /// Battle type is neither BATTLE_TYPE_RANGE nor BATTLE_TYPE_FEE.
+    error UnknownBattleType(uint8 battleType);
+    /// A tick is outside the Uniswap [MIN_TICK, MAX_TICK] range.
+    error TickOutOfRange();
+    /// Position bounds do not satisfy tickLower < tickUpper.
+    error InvalidTickRange(int32 tickLower, int32 tickUpper);
+    /// Battle window does not satisfy start < end.
+    error InvalidBattleWindow(uint256 start, uint256 end);
+    /// Checkpoint timestamps are not strictly increasing at this index.
+    error CheckpointsOutOfOrder(uint256 index);
+    /// No checkpoint at or before the battle start.
+    error CheckpointsDoNotCover(uint256 start, uint256 firstTimestamp);
+    /// Two consecutive checkpoints (or the last one and the battle end) are too far apart.
+    error CheckpointGapTooLarge(uint256 from, uint256 to);
+    /// A snapshot tick cumulative does not fit int56.
+    error CumulativeOutOfRange();
+    /// Liquidity share cap must be in (0, 10000] bps.
+    error InvalidLiquidityShareCap(uint256 capBps);
+    /// Pool-wide fee APR must be non-zero to score against the pool.
+    error ZeroPoolFeeApr();
+    /// A fixed-point division had a zero denominator.
+    error DivisionByZero();
+    /// A fixed-point function was evaluated outside its domain (e.g. ln(0)).
+    error MathUndefined();
+}
+
+/// Errors returned by the scoring engine, ABI-encoded as Solidity custom errors.
+#[derive(SolidityError, Debug, PartialEq, Eq)]
+pub enum ScoringError {
+    InRangeExceedsTotal(InRangeExceedsTotal),
+    Overflow(Overflow),
+    ZeroDuration(ZeroDuration),
+    ZeroLpValue(ZeroLpValue),
+    UnknownDex(UnknownDex),
+    DexDisabled(DexDisabled),
+    DexAlreadyRegistered(DexAlreadyRegistered),
+    DexNotInParamSet(DexNotInParamSet),
+    UnknownParamSet(UnknownParamSet),
+    NotOwner(NotOwner),
+    ZeroAddressOwner(ZeroAddressOwner),
+    InvalidDexWeight(InvalidDexWeight),
+    InvalidRangeParams(InvalidRangeParams),
+    InvalidResolverBpsLimits(InvalidResolverBpsLimits),
+    ResolverBpsOutsideLimits(ResolverBpsOutsideLimits),
+    InvalidDrawTolerance(InvalidDrawTolerance),
+    RewardBpsOutOfRange(RewardBpsOutOfRange),
+    InvalidPlayerCount(InvalidPlayerCount),
+    InvalidPayoutCurve(InvalidPayoutCurve),
+    InvalidPlacements(InvalidPlacements),
+    InvalidHybridParams(InvalidHybridParams),
+    InvalidDecimals(InvalidDecimals),
+    UnknownBattleType(UnknownBattleType),
+    TickOutOfRange(TickOutOfRange),
+    InvalidTickRange(InvalidTickRange),
+    InvalidBattleWindow(InvalidBattleWindow),
+    CheckpointsOutOfOrder(CheckpointsOutOfOrder),
+    CheckpointsDoNotCover(CheckpointsDoNotCover),
+    CheckpointGapTooLarge(CheckpointGapTooLarge),
+    CumulativeOutOfRange(CumulativeOutOfRange),
+    InvalidLiquidityShareCap(InvalidLiquidityShareCap),
+    ZeroPoolFeeApr(ZeroPoolFeeApr),
+    DivisionByZero(DivisionByZero),
+    MathUndefined(MathUndefined),
+}
+
+impl From<MathError> for ScoringError {
+    fn from(err: MathError) -> Self {
+        match err {
+            MathError::Overflow => ScoringError::Overflow(Overflow {}),
+            MathError::DivisionByZero => ScoringError::DivisionByZero(DivisionByZero {}),
+            MathError::Undefined => ScoringError::MathUndefined(MathUndefined {}),
+            MathError::TickOutOfRange => ScoringError::TickOutOfRange(TickOutOfRange {}),
+        }
+    }
+}
+
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), not(feature = "views")))]
+sol! {
+    event OwnershipTransferred(address indexed previousOwner, address indexed newOwner);
+    event DexRegistered(uint8 indexed dexType, string name, uint256 weightBps);
+    event DexWeightUpdated(uint8 indexed dexType, uint256 oldWeightBps, uint256 newWeightBps);
+    event DexStatusUpdated(uint8 indexed dexType, bool enabled);
+    event RangeParamsUpdated(uint256 threshold, uint256 maxBonus);
+    event ResolverBpsLimitsUpdated(uint256 minBps, uint256 maxBps);
+    event ParamSetPublished(uint256 indexed version);
+    event DrawToleranceUpdated(uint256 oldToleranceBps, uint256 newToleranceBps);
+}
+
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), not(feature = "views")))]
 sol_storage! {
     #[entrypoint]
     pub struct BattleScoring {
-        /// Owner for future upgrades (unused in pure functions but reserved)
+        /// Owner allowed to update scoring parameters
         address owner;
+
+        /// DEX registry (dex_type => config)
+        mapping(uint8 => DexConfig) dexes;
+
+        /// Registered DEX types in registration order
+        uint8[] dex_types;
+
+        /// Tick distance below which a range position earns a tightness bonus
+        uint256 range_threshold;
+
+        /// Maximum tightness bonus (1e18 = 100%)
+        uint256 range_max_bonus;
+
+        /// Lowest resolver reward accepted by versioned reward calculation
+        uint256 min_resolver_bps;
+
+        /// Highest resolver reward accepted by versioned reward calculation
+        uint256 max_resolver_bps;
+
+        /// Published, immutable parameter sets (index = version)
+        ParamSet[] param_sets;
+
+        /// Relative score difference (bps of the higher score) treated as a draw
+        uint256 draw_tolerance_bps;
+    }
+
+    pub struct ParamSet {
+        uint256 range_threshold;
+        uint256 range_max_bonus;
+        uint256 min_resolver_bps;
+        uint256 max_resolver_bps;
+
+        /// DEX weights at publish time (dex_type => bps). Zero = not available.
+        mapping(uint8 => uint256) dex_weight_bps;
+
+        uint256 draw_tolerance_bps;
+    }
+
+    pub struct DexConfig {
+        /// Human-readable DEX name (e.g. "Uniswap V4")
+        string name;
+
+        /// Normalization weight in basis points (10000 = 1.0x)
+        uint256 weight_bps;
+
+        /// Whether scores from this DEX are currently accepted
+        bool enabled;
+
+        /// Whether this dex_type has been registered
+        bool registered;
     }
 }
 
-#[cfg(any(target_arch = "wasm32", feature = "export-abi"))]
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), not(feature = "views")))]
 #[public]
 impl BattleScoring {
     /// Calculate score for a range battle.
@@ -43,8 +307,14 @@ impl BattleScoring {
         in_range_time: U256,
         total_time: U256,
         tick_distance: U256,
-    ) -> U256 {
-        range_score(in_range_time, total_time, tick_distance)
+    ) -> Result<U256, ScoringError> {
+        range_score_with_params(
+            in_range_time,
+            total_time,
+            tick_distance,
+            self.range_threshold.get(),
+            self.range_max_bonus.get(),
+        )
     }
 
     /// Calculate score for a fee battle.
@@ -53,7 +323,7 @@ impl BattleScoring {
         fees_usd: U256,
         lp_value_usd: U256,
         duration: U256,
-    ) -> U256 {
+    ) -> Result<U256, ScoringError> {
         fee_score(fees_usd, lp_value_usd, duration)
     }
 
@@ -62,274 +332,2992 @@ impl BattleScoring {
         winner(score_a, score_b)
     }
 
+    /// Determine the battle outcome from two scores: 0 = draw, 1 = player A, 2 = player B.
+    /// Scores within the configured draw tolerance count as a draw.
+    pub fn determine_outcome(&self, score_a: U256, score_b: U256) -> u8 {
+        outcome(score_a, score_b, self.draw_tolerance_bps.get())
+    }
+
     /// Calculate reward distribution amounts.
     pub fn calculate_rewards(
         &self,
         total_fees: U256,
         resolver_bps: U256,
-    ) -> (U256, U256) {
+    ) -> Result<(U256, U256), ScoringError> {
         rewards(total_fees, resolver_bps)
     }
 
+    /// Calculate reward distribution for a draw as (playerAAmount, playerBAmount, resolverAmount).
+    pub fn calculate_draw_rewards(
+        &self,
+        total_fees: U256,
+        resolver_bps: U256,
+    ) -> Result<(U256, U256, U256), ScoringError> {
+        draw_rewards(total_fees, resolver_bps)
+    }
+
     /// Normalize a score for cross-DEX fairness.
-    pub fn normalize_cross_dex(&self, raw_score: U256, dex_type: u8) -> U256 {
-        normalize_cross_dex(raw_score, dex_type)
+    /// Reverts for unregistered or disabled DEX types.
+    pub fn normalize_cross_dex(&self, raw_score: U256, dex_type: u8) -> Result<U256, ScoringError> {
+        normalize_cross_dex(raw_score, self.active_dex_weight(dex_type)?)
     }
-}
 
-// ============ Pure logic functions (testable without Stylus VM) ============
+    /// Set the owner, register Uniswap V4 and Camelot V3 at 1.0x and store the
+    /// default parameters. Runs once, inside the deployment transaction, so no
+    /// one can claim ownership first; later changes go through `transfer_ownership`.
+    #[constructor]
+    pub fn constructor(&mut self, owner: Address) -> Result<(), ScoringError> {
+        if owner == Address::ZERO {
+            return Err(ScoringError::ZeroAddressOwner(ZeroAddressOwner {}));
+        }
 
-/// Calculate range score: (inRangeTime / totalTime) * 1e18, with tick tightness bonus.
-pub fn range_score(in_range_time: U256, total_time: U256, tick_distance: U256) -> U256 {
-    if total_time.is_zero() {
-        return U256::ZERO;
-    }
+        self.store_range_params(U256::from(TIGHT_RANGE_THRESHOLD), U256::from(TIGHT_RANGE_BONUS));
+        self.store_resolver_bps_limits(U256::ZERO, U256::from(MAX_BPS));
+        self.draw_tolerance_bps.set(U256::from(DEFAULT_DRAW_TOLERANCE_BPS));
+        self.store_owner(owner);
 
-    let decimals = U256::from(SCORE_DECIMALS);
+        for (dex_type, name) in [(DEX_UNISWAP_V4, "Uniswap V4"), (DEX_CAMELOT_V3, "Camelot V3")] {
+            self.store_new_dex(dex_type, String::from(name), U256::from(DEFAULT_DEX_WEIGHT_BPS));
+        }
+        Ok(())
+    }
 
-    // Base score: (inRangeTime * 1e18) / totalTime
-    let base_score = (in_range_time * decimals) / total_time;
+    /// Hand ownership to a new, non-zero address (owner only).
+    pub fn transfer_ownership(&mut self, new_owner: Address) -> Result<(), ScoringError> {
+        self.only_owner()?;
+        if new_owner == Address::ZERO {
+            return Err(ScoringError::ZeroAddressOwner(ZeroAddressOwner {}));
+        }
+        self.store_owner(new_owner);
+        Ok(())
+    }
 
-    // Tick distance bonus: tighter ranges get up to 20% bonus
-    let threshold = U256::from(TIGHT_RANGE_THRESHOLD);
-    let max_bonus = U256::from(TIGHT_RANGE_BONUS);
+    /// Register a new DEX type with a name and weight (owner only).
+    pub fn register_dex(
+        &mut self,
+        dex_type: u8,
+        name: String,
+        weight_bps: U256,
+    ) -> Result<(), ScoringError> {
+        self.only_owner()?;
+        if self.dexes.getter(U8::from(dex_type)).registered.get() {
+            return Err(ScoringError::DexAlreadyRegistered(DexAlreadyRegistered { dexType: dex_type }));
+        }
+        if !is_valid_dex_weight(weight_bps) {
+            return Err(ScoringError::InvalidDexWeight(InvalidDexWeight { weightBps: weight_bps }));
+        }
+        self.store_new_dex(dex_type, name, weight_bps);
+        Ok(())
+    }
 
-    let bonus = if tick_distance < threshold {
-        // Linear bonus: bonus = maxBonus * (threshold - tickDistance) / threshold
-        max_bonus * (threshold - tick_distance) / threshold
-    } else {
-        U256::ZERO
-    };
+    /// Set the normalization weight for a registered DEX (owner only).
+    pub fn set_dex_weight(&mut self, dex_type: u8, weight_bps: U256) -> Result<(), ScoringError> {
+        self.only_owner()?;
+        if !is_valid_dex_weight(weight_bps) {
+            return Err(ScoringError::InvalidDexWeight(InvalidDexWeight { weightBps: weight_bps }));
+        }
 
-    // Final score = baseScore + (baseScore * bonus / 1e18)
-    base_score + (base_score * bonus / decimals)
-}
+        let mut dex = self.dexes.setter(U8::from(dex_type));
+        if !dex.registered.get() {
+            return Err(unknown_dex(dex_type));
+        }
+        let old_weight = dex.weight_bps.get();
+        dex.weight_bps.set(weight_bps);
 
-/// Calculate fee yield rate: (feesUSD * 1e18) / (lpValueUSD * duration)
-pub fn fee_score(fees_usd: U256, lp_value_usd: U256, duration: U256) -> U256 {
-    if lp_value_usd.is_zero() || duration.is_zero() {
-        return U256::ZERO;
+        self.vm().log(DexWeightUpdated {
+            dexType: dex_type,
+            oldWeightBps: old_weight,
+            newWeightBps: weight_bps,
+        });
+        Ok(())
     }
-    let decimals = U256::from(SCORE_DECIMALS);
-    (fees_usd * decimals) / (lp_value_usd * duration)
-}
 
-/// Determine winner: 1 = player A, 2 = player B. Tie goes to A.
-pub fn winner(score_a: U256, score_b: U256) -> u8 {
-    if score_a >= score_b { 1 } else { 2 }
-}
+    /// Enable or disable a registered DEX (owner only).
+    pub fn set_dex_enabled(&mut self, dex_type: u8, enabled: bool) -> Result<(), ScoringError> {
+        self.only_owner()?;
 
-/// Calculate rewards: (winnerAmount, resolverAmount) from total fees.
-pub fn rewards(total_fees: U256, resolver_bps: U256) -> (U256, U256) {
-    let max_bps = U256::from(MAX_BPS);
-    if resolver_bps >= max_bps {
-        return (U256::ZERO, total_fees);
-    }
-    let resolver_amount = (total_fees * resolver_bps) / max_bps;
-    let winner_amount = total_fees - resolver_amount;
-    (winner_amount, resolver_amount)
-}
+        let mut dex = self.dexes.setter(U8::from(dex_type));
+        if !dex.registered.get() {
+            return Err(unknown_dex(dex_type));
+        }
+        dex.enabled.set(enabled);
 
-/// Normalize a score by applying a DEX-specific weight factor.
-/// dex_type 0 = UNISWAP_V4, 1 = CAMELOT_V3.
-/// Weight is in basis points: 10000 = 1.0x, 11000 = 1.1x, 9000 = 0.9x.
-/// Unknown DEX types get no adjustment (1.0x).
-pub fn normalize_cross_dex(raw_score: U256, dex_type: u8) -> U256 {
-    let bps = U256::from(MAX_BPS);
-    let weight = if (dex_type as usize) < DEX_WEIGHT_BPS.len() {
-        U256::from(DEX_WEIGHT_BPS[dex_type as usize])
-    } else {
-        bps // unknown DEX → 1.0x (no adjustment)
-    };
-    (raw_score * weight) / bps
-}
+        self.vm().log(DexStatusUpdated {
+            dexType: dex_type,
+            enabled,
+        });
+        Ok(())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Get a registered DEX as (name, weightBps, enabled).
+    pub fn get_dex(&self, dex_type: u8) -> Result<(String, U256, bool), ScoringError> {
+        let dex = self.dexes.getter(U8::from(dex_type));
+        if !dex.registered.get() {
+            return Err(unknown_dex(dex_type));
+        }
+        Ok((dex.name.get_string(), dex.weight_bps.get(), dex.enabled.get()))
+    }
 
-    const E18: u64 = 1_000_000_000_000_000_000;
+    /// Get the normalization weight for a registered DEX in basis points.
+    pub fn get_dex_weight(&self, dex_type: u8) -> Result<U256, ScoringError> {
+        let dex = self.dexes.getter(U8::from(dex_type));
+        if !dex.registered.get() {
+            return Err(unknown_dex(dex_type));
+        }
+        Ok(dex.weight_bps.get())
+    }
 
-    // ============ Range Score Tests ============
+    /// Set the range tightness threshold and maximum bonus (owner only).
+    pub fn set_range_params(&mut self, threshold: U256, max_bonus: U256) -> Result<(), ScoringError> {
+        self.only_owner()?;
+        if !is_valid_range_params(threshold, max_bonus) {
+            return Err(ScoringError::InvalidRangeParams(InvalidRangeParams {
+                threshold,
+                maxBonus: max_bonus,
+            }));
+        }
+        self.store_range_params(threshold, max_bonus);
+        Ok(())
+    }
 
-    #[test]
-    fn test_range_score_full_time_no_bonus() {
-        let score = range_score(
-            U256::from(3600u64),
-            U256::from(3600u64),
-            U256::from(200u64), // beyond threshold
-        );
-        assert_eq!(score, U256::from(E18));
+    /// Get the range parameters as (threshold, maxBonus).
+    pub fn get_range_params(&self) -> (U256, U256) {
+        (self.range_threshold.get(), self.range_max_bonus.get())
     }
 
-    #[test]
-    fn test_range_score_half_time() {
-        let score = range_score(
-            U256::from(1800u64),
-            U256::from(3600u64),
-            U256::from(200u64),
-        );
-        assert_eq!(score, U256::from(E18 / 2));
+    /// Set the resolver reward bounds used by future parameter sets (owner only).
+    pub fn set_resolver_bps_limits(&mut self, min_bps: U256, max_bps: U256) -> Result<(), ScoringError> {
+        self.only_owner()?;
+        if !is_valid_resolver_bps_limits(min_bps, max_bps) {
+            return Err(ScoringError::InvalidResolverBpsLimits(InvalidResolverBpsLimits {
+                minBps: min_bps,
+                maxBps: max_bps,
+            }));
+        }
+        self.store_resolver_bps_limits(min_bps, max_bps);
+        Ok(())
     }
 
-    #[test]
-    fn test_range_score_zero_total_time() {
-        let score = range_score(U256::from(1000u64), U256::ZERO, U256::ZERO);
-        assert_eq!(score, U256::ZERO);
+    /// Get the resolver reward bounds as (minBps, maxBps).
+    pub fn get_resolver_bps_limits(&self) -> (U256, U256) {
+        (self.min_resolver_bps.get(), self.max_resolver_bps.get())
     }
 
-    #[test]
-    fn test_range_score_zero_in_range() {
-        let score = range_score(U256::ZERO, U256::from(3600u64), U256::from(50u64));
-        assert_eq!(score, U256::ZERO);
+    /// Snapshot the current parameters into a new immutable version (owner only).
+    /// Returns the new version number.
+    pub fn publish_param_set(&mut self) -> Result<U256, ScoringError> {
+        self.only_owner()?;
+
+        let dex_weights: Vec<(u8, U256)> = self
+            .get_registered_dexes()
+            .into_iter()
+            .filter_map(|dex_type| self.active_dex_weight(dex_type).ok().map(|w| (dex_type, w)))
+            .collect();
+
+        let range_threshold = self.range_threshold.get();
+        let range_max_bonus = self.range_max_bonus.get();
+        let min_resolver_bps = self.min_resolver_bps.get();
+        let max_resolver_bps = self.max_resolver_bps.get();
+        let draw_tolerance_bps = self.draw_tolerance_bps.get();
+
+        let version = U256::from(self.param_sets.len());
+        let mut set = self.param_sets.grow();
+        set.range_threshold.set(range_threshold);
+        set.range_max_bonus.set(range_max_bonus);
+        set.min_resolver_bps.set(min_resolver_bps);
+        set.max_resolver_bps.set(max_resolver_bps);
+        set.draw_tolerance_bps.set(draw_tolerance_bps);
+        for (dex_type, weight) in dex_weights {
+            set.dex_weight_bps.setter(U8::from(dex_type)).set(weight);
+        }
+
+        self.vm().log(ParamSetPublished { version });
+        Ok(version)
     }
 
-    #[test]
-    fn test_range_score_tight_range_max_bonus() {
-        // tick_distance = 0 → max bonus (20%)
-        let score = range_score(
-            U256::from(3600u64),
-            U256::from(3600u64),
-            U256::ZERO,
-        );
-        let expected = U256::from(E18) + U256::from(TIGHT_RANGE_BONUS);
-        assert_eq!(score, expected);
+    /// Get the number of published parameter sets.
+    pub fn get_param_set_count(&self) -> U256 {
+        U256::from(self.param_sets.len())
     }
 
-    #[test]
-    fn test_range_score_tight_range_half_bonus() {
-        // tick_distance = 50 → half of max bonus
-        let score = range_score(
-            U256::from(3600u64),
-            U256::from(3600u64),
-            U256::from(50u64),
-        );
-        let half_bonus = TIGHT_RANGE_BONUS / 2;
-        let expected = U256::from(E18) + U256::from(half_bonus);
-        assert_eq!(score, expected);
+    /// Get a published parameter set as
+    /// (threshold, maxBonus, minResolverBps, maxResolverBps, drawToleranceBps).
+    #[allow(clippy::type_complexity)]
+    pub fn get_param_set(&self, version: U256) -> Result<(U256, U256, U256, U256, U256), ScoringError> {
+        let set = self.param_set(version)?;
+        Ok((
+            set.range_threshold.get(),
+            set.range_max_bonus.get(),
+            set.min_resolver_bps.get(),
+            set.max_resolver_bps.get(),
+            set.draw_tolerance_bps.get(),
+        ))
     }
 
-    #[test]
-    fn test_range_score_at_threshold_no_bonus() {
-        let score = range_score(
-            U256::from(3600u64),
-            U256::from(3600u64),
-            U256::from(TIGHT_RANGE_THRESHOLD),
-        );
-        assert_eq!(score, U256::from(E18));
+    /// Get the DEX weight pinned in a published parameter set (0 = not available).
+    pub fn get_param_set_dex_weight(&self, version: U256, dex_type: u8) -> Result<U256, ScoringError> {
+        Ok(self.param_set(version)?.dex_weight_bps.get(U8::from(dex_type)))
     }
 
-    #[test]
-    fn test_range_score_partial_time_with_bonus() {
-        // 50% in range, max bonus
-        let score = range_score(
-            U256::from(1800u64),
-            U256::from(3600u64),
-            U256::ZERO,
-        );
-        let base = E18 / 2;
-        let bonus = TIGHT_RANGE_BONUS / 2;
-        let expected = U256::from(base) + U256::from(bonus);
-        assert_eq!(score, expected);
+    /// Calculate a range battle score under a published parameter set.
+    pub fn calculate_range_score_v(
+        &self,
+        version: U256,
+        in_range_time: U256,
+        total_time: U256,
+        tick_distance: U256,
+    ) -> Result<U256, ScoringError> {
+        let set = self.param_set(version)?;
+        range_score_with_params(
+            in_range_time,
+            total_time,
+            tick_distance,
+            set.range_threshold.get(),
+            set.range_max_bonus.get(),
+        )
     }
 
-    // ============ Fee Score Tests ============
+    /// Normalize a score for cross-DEX fairness under a published parameter set.
+    pub fn normalize_cross_dex_v(
+        &self,
+        version: U256,
+        raw_score: U256,
+        dex_type: u8,
+    ) -> Result<U256, ScoringError> {
+        let weight = self.param_set(version)?.dex_weight_bps.get(U8::from(dex_type));
+        normalize_cross_dex(raw_score, pinned_dex_weight(dex_type, weight)?)
+    }
 
-    #[test]
-    fn test_fee_score_basic() {
-        let score = fee_score(
-            U256::from(10u64) * U256::from(100_000_000u64), // $10
-            U256::from(1000u64) * U256::from(100_000_000u64), // $1000
-            U256::from(3600u64),
-        );
-        let expected = U256::from(E18) / U256::from(360_000u64);
-        assert_eq!(score, expected);
+    /// Calculate reward distribution under a published parameter set.
+    /// Reverts if resolver_bps is outside the set's resolver limits.
+    pub fn calculate_rewards_v(
+        &self,
+        version: U256,
+        total_fees: U256,
+        resolver_bps: U256,
+    ) -> Result<(U256, U256), ScoringError> {
+        let set = self.param_set(version)?;
+        let (min_bps, max_bps) = (set.min_resolver_bps.get(), set.max_resolver_bps.get());
+        if !resolver_bps_within_limits(resolver_bps, min_bps, max_bps) {
+            return Err(ScoringError::ResolverBpsOutsideLimits(ResolverBpsOutsideLimits {
+                resolverBps: resolver_bps,
+                minBps: min_bps,
+                maxBps: max_bps,
+            }));
+        }
+        rewards(total_fees, resolver_bps)
     }
 
-    #[test]
-    fn test_fee_score_zero_fees() {
-        assert_eq!(fee_score(U256::ZERO, U256::from(1000u64), U256::from(3600u64)), U256::ZERO);
+    /// Determine the battle outcome under a published parameter set's draw tolerance.
+    pub fn determine_outcome_v(
+        &self,
+        version: U256,
+        score_a: U256,
+        score_b: U256,
+    ) -> Result<u8, ScoringError> {
+        Ok(outcome(score_a, score_b, self.param_set(version)?.draw_tolerance_bps.get()))
     }
 
-    #[test]
-    fn test_fee_score_zero_lp_value() {
-        assert_eq!(fee_score(U256::from(100u64), U256::ZERO, U256::from(3600u64)), U256::ZERO);
+    /// Set the relative tolerance under which scores count as a draw (owner only).
+    pub fn set_draw_tolerance(&mut self, tolerance_bps: U256) -> Result<(), ScoringError> {
+        self.only_owner()?;
+        if !is_valid_draw_tolerance(tolerance_bps) {
+            return Err(ScoringError::InvalidDrawTolerance(InvalidDrawTolerance {
+                toleranceBps: tolerance_bps,
+            }));
+        }
+
+        let old_tolerance = self.draw_tolerance_bps.get();
+        self.draw_tolerance_bps.set(tolerance_bps);
+
+        self.vm().log(DrawToleranceUpdated {
+            oldToleranceBps: old_tolerance,
+            newToleranceBps: tolerance_bps,
+        });
+        Ok(())
     }
 
-    #[test]
-    fn test_fee_score_zero_duration() {
-        assert_eq!(fee_score(U256::from(100u64), U256::from(1000u64), U256::ZERO), U256::ZERO);
+    /// Get the draw tolerance in basis points of the higher score.
+    pub fn get_draw_tolerance(&self) -> U256 {
+        self.draw_tolerance_bps.get()
     }
 
-    #[test]
-    fn test_fee_score_higher_fees_higher_score() {
-        let low = fee_score(U256::from(10u64), U256::from(1000u64), U256::from(3600u64));
-        let high = fee_score(U256::from(100u64), U256::from(1000u64), U256::from(3600u64));
-        assert!(high > low);
+    /// Get all registered DEX types in registration order.
+    pub fn get_registered_dexes(&self) -> Vec<u8> {
+        (0..self.dex_types.len())
+            .filter_map(|i| self.dex_types.get(i))
+            .map(|dex_type| dex_type.to::<u8>())
+            .collect()
     }
 
-    #[test]
-    fn test_fee_score_larger_position_lower_rate() {
-        let small = fee_score(U256::from(100u64), U256::from(1000u64), U256::from(3600u64));
-        let large = fee_score(U256::from(100u64), U256::from(2000u64), U256::from(3600u64));
-        assert!(small > large);
+    /// Get the owner address.
+    pub fn get_owner(&self) -> Address {
+        self.owner.get()
     }
+}
 
-    // ============ Determine Winner Tests ============
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), feature = "views"))]
+sol_storage! {
+    /// Stateless calculators built with `--features views` and deployed beside the
+    /// engine, so they do not count against its code size limit
+    #[entrypoint]
+    pub struct ScoringViews {}
+}
 
-    #[test]
-    fn test_winner_a_higher() {
-        assert_eq!(winner(U256::from(100u64), U256::from(50u64)), 1);
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), feature = "views"))]
+#[public]
+impl ScoringViews {
+    /// Calculate a fee score from raw token amounts.
+    /// Each token's fees are valued as `fees * price / 10^(decimals + priceDecimals - 8)`
+    /// (8-decimal USD, rounded down), summed, and scored with `calculate_fee_score`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_fee_score_from_tokens(
+        &self,
+        fees0: U256,
+        decimals0: u8,
+        price0: U256,
+        price_decimals0: u8,
+        fees1: U256,
+        decimals1: u8,
+        price1: U256,
+        price_decimals1: u8,
+        lp_value_usd: U256,
+        duration: U256,
+    ) -> Result<U256, ScoringError> {
+        let token0 = TokenFees {
+            amount: fees0,
+            decimals: decimals0,
+            price: price0,
+            price_decimals: price_decimals0,
+        };
+        let token1 = TokenFees {
+            amount: fees1,
+            decimals: decimals1,
+            price: price1,
+            price_decimals: price_decimals1,
+        };
+        fee_score_from_tokens(&token0, &token1, lp_value_usd, duration)
     }
 
-    #[test]
-    fn test_winner_b_higher() {
-        assert_eq!(winner(U256::from(50u64), U256::from(100u64)), 2);
+    /// Calculate a fee score from raw token amounts with donations removed, for
+    /// Uniswap V4 pools where `donate()` raises fee growth for in-range positions.
+    /// `donation_growth0/1_x128` is the donated fee growth per unit of liquidity the
+    /// position's range accrued during the battle window, as recorded by
+    /// `BattleVaultHook` at each `donate()`; growth * `position_liquidity` is removed
+    /// from each token's fees before they are valued and scored like
+    /// `calculate_fee_score_from_tokens`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_donation_adjusted_fee_score(
+        &self,
+        fees0: U256,
+        donation_growth0_x128: U256,
+        decimals0: u8,
+        price0: U256,
+        price_decimals0: u8,
+        fees1: U256,
+        donation_growth1_x128: U256,
+        decimals1: u8,
+        price1: U256,
+        price_decimals1: u8,
+        position_liquidity: u128,
+        lp_value_usd: U256,
+        duration: U256,
+    ) -> Result<U256, ScoringError> {
+        let token0 = TokenFees {
+            amount: donation_adjusted_fees(fees0, donation_growth0_x128, position_liquidity)?,
+            decimals: decimals0,
+            price: price0,
+            price_decimals: price_decimals0,
+        };
+        let token1 = TokenFees {
+            amount: donation_adjusted_fees(fees1, donation_growth1_x128, position_liquidity)?,
+            decimals: decimals1,
+            price: price1,
+            price_decimals: price_decimals1,
+        };
+        fee_score_from_tokens(&token0, &token1, lp_value_usd, duration)
     }
 
-    #[test]
-    fn test_winner_tie_goes_to_a() {
-        assert_eq!(winner(U256::from(100u64), U256::from(100u64)), 1);
+    /// Calculate a "beat the pool" fee score: the position's annualized fee yield
+    /// divided by the pool-wide fee APR over the same window, in 1e18 (1e18 =
+    /// matched the pool). `pool_fee_apr` is the pool's own fee APR, so its fee
+    /// tier or Camelot/Algebra dynamic fee is already priced in; zero reverts.
+    pub fn calculate_pool_relative_fee_score(
+        &self,
+        fees_usd: U256,
+        lp_value_usd: U256,
+        duration: U256,
+        pool_fee_apr: U256,
+    ) -> Result<U256, ScoringError> {
+        pool_relative_fee_score(fees_usd, lp_value_usd, duration, pool_fee_apr)
     }
 
-    #[test]
-    fn test_winner_both_zero() {
-        assert_eq!(winner(U256::ZERO, U256::ZERO), 1);
+    /// Fees owed to a position between two `feeGrowthInside0/1X128` snapshots, as
+    /// (fees0, fees1) in raw token amounts. Growth is subtracted modulo 2^256, exactly
+    /// as V3/V4 positions do, so an accumulator that wrapped mid-battle is still correct.
+    pub fn calculate_fees_owed(
+        &self,
+        start_fee_growth0_x128: U256,
+        start_fee_growth1_x128: U256,
+        end_fee_growth0_x128: U256,
+        end_fee_growth1_x128: U256,
+        liquidity: u128,
+    ) -> Result<(U256, U256), ScoringError> {
+        position_fees_owed(
+            start_fee_growth0_x128,
+            start_fee_growth1_x128,
+            end_fee_growth0_x128,
+            end_fee_growth1_x128,
+            liquidity,
+        )
     }
 
-    // ============ Calculate Rewards Tests ============
+    /// Calculate a fee score straight from fee growth snapshots: fees owed are
+    /// computed as in `calculate_fees_owed`, valued with 8-decimal USD prices per
+    /// whole token, and scored with `calculate_fee_score`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_fee_score_from_fee_growth(
+        &self,
+        start_fee_growth0_x128: U256,
+        start_fee_growth1_x128: U256,
+        end_fee_growth0_x128: U256,
+        end_fee_growth1_x128: U256,
+        liquidity: u128,
+        price0: U256,
+        price1: U256,
+        dec0: u8,
+        dec1: u8,
+        lp_value_usd: U256,
+        duration: U256,
+    ) -> Result<U256, ScoringError> {
+        let (fees0, fees1) = position_fees_owed(
+            start_fee_growth0_x128,
+            start_fee_growth1_x128,
+            end_fee_growth0_x128,
+            end_fee_growth1_x128,
+            liquidity,
+        )?;
+        let token0 = TokenFees {
+            amount: fees0,
+            decimals: dec0,
+            price: price0,
+            price_decimals: USD_DECIMALS,
+        };
+        let token1 = TokenFees {
+            amount: fees1,
+            decimals: dec1,
+            price: price1,
+            price_decimals: USD_DECIMALS,
+        };
+        fee_score_from_tokens(&token0, &token1, lp_value_usd, duration)
+    }
 
-    #[test]
-    fn test_rewards_standard_1_percent() {
-        let (w, r) = rewards(U256::from(10000u64), U256::from(100u64));
-        assert_eq!(r, U256::from(100u64));
-        assert_eq!(w, U256::from(9900u64));
+    /// Calculate an impermanent-loss-aware net PnL score for a concentrated
+    /// liquidity position: (LP value + collected fees) / HODL value at the exit
+    /// price, in 1e18 (1e18 = matched holding the entry tokens). Intended for a
+    /// future net-PnL `BattleType`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_net_pnl_score(
+        &self,
+        entry_sqrt_price_x96: U256,
+        exit_sqrt_price_x96: U256,
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity: u128,
+        fees0: U256,
+        fees1: U256,
+    ) -> Result<U256, ScoringError> {
+        net_pnl_score(&NetPnlInput {
+            entry_sqrt_price_x96,
+            exit_sqrt_price_x96,
+            tick_lower,
+            tick_upper,
+            liquidity,
+            fees0,
+            fees1,
+        })
     }
 
-    #[test]
-    fn test_rewards_10_percent() {
-        let (w, r) = rewards(U256::from(10000u64), U256::from(1000u64));
-        assert_eq!(r, U256::from(1000u64));
-        assert_eq!(w, U256::from(9000u64));
+    /// Value a concentrated liquidity position as (amount0, amount1, valueUsd).
+    /// Prices are 8-decimal USD per whole token (Chainlink feeds); `dec0`/`dec1`
+    /// are the token decimals. The value is 8-decimal USD, rounded down.
+    #[allow(clippy::too_many_arguments)]
+    pub fn position_value_usd(
+        &self,
+        liquidity: u128,
+        tick_lower: i32,
+        tick_upper: i32,
+        sqrt_price_x96: U256,
+        price0: U256,
+        price1: U256,
+        dec0: u8,
+        dec1: u8,
+    ) -> Result<(U256, U256, U256), ScoringError> {
+        let value = position_value_usd(
+            liquidity,
+            tick_lower,
+            tick_upper,
+            sqrt_price_x96,
+            price0,
+            price1,
+            dec0,
+            dec1,
+        )?;
+        Ok((value.amount0, value.amount1, value.value_usd))
     }
 
-    #[test]
-    fn test_rewards_zero_fees() {
-        let (w, r) = rewards(U256::ZERO, U256::from(100u64));
-        assert_eq!(w, U256::ZERO);
-        assert_eq!(r, U256::ZERO);
+    /// Check that two values differ by at most `bps` of the larger one,
+    /// as `BattleArena._validateValueTolerance`.
+    pub fn value_within_tolerance(&self, a: U256, b: U256, bps: U256) -> Result<bool, ScoringError> {
+        value_within_tolerance(a, b, bps)
     }
 
-    #[test]
-    fn test_rewards_zero_resolver_bps() {
-        let (w, r) = rewards(U256::from(10000u64), U256::ZERO);
-        assert_eq!(w, U256::from(10000u64));
-        assert_eq!(r, U256::ZERO);
+    /// Break a tie between two players as (winner, rule).
+    /// Rules are tried in order: narrower tick width, lower USD value at stake,
+    /// earlier join timestamp, then a coin flip derived from `seed`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn break_tie(
+        &self,
+        tick_width_a: U256,
+        tick_width_b: U256,
+        value_usd_a: U256,
+        value_usd_b: U256,
+        joined_at_a: U256,
+        joined_at_b: U256,
+        seed: U256,
+    ) -> (u8, u8) {
+        break_tie(
+            &TieBreakMetrics {
+                tick_width: tick_width_a,
+                value_usd: value_usd_a,
+                joined_at: joined_at_a,
+            },
+            &TieBreakMetrics {
+                tick_width: tick_width_b,
+                value_usd: value_usd_b,
+                joined_at: joined_at_b,
+            },
+            seed,
+        )
     }
 
-    #[test]
+    /// Calculate a three-way reward split as (winnerAmount, resolverAmount, protocolAmount).
+    /// Reverts if resolver_bps + protocol_bps exceeds max_combined_bps or max_combined_bps exceeds 10000.
+    pub fn calculate_rewards_v2(
+        &self,
+        total_fees: U256,
+        resolver_bps: U256,
+        protocol_bps: U256,
+        max_combined_bps: U256,
+    ) -> Result<(U256, U256, U256), ScoringError> {
+        rewards_v2(total_fees, resolver_bps, protocol_bps, max_combined_bps)
+    }
+
+    /// Split fees collected in both pool tokens as
+    /// (winner0, winner1, resolver0, resolver1, protocol0, protocol1).
+    /// Reverts if resolver_bps + protocol_bps exceeds 10000.
+    pub fn calculate_pair_rewards(
+        &self,
+        fees0: U256,
+        fees1: U256,
+        resolver_bps: U256,
+        protocol_bps: U256,
+    ) -> Result<(U256, U256, U256, U256, U256, U256), ScoringError> {
+        let split = pair_rewards(fees0, fees1, resolver_bps, protocol_bps)?;
+        Ok((
+            split.winner0,
+            split.winner1,
+            split.resolver0,
+            split.resolver1,
+            split.protocol0,
+            split.protocol1,
+        ))
+    }
+
+    /// Split fees in proportion to each player's score as (playerAAmount, playerBAmount).
+    pub fn calculate_proportional_rewards(
+        &self,
+        score_a: U256,
+        score_b: U256,
+        total_fees: U256,
+    ) -> Result<(U256, U256), ScoringError> {
+        proportional_rewards(score_a, score_b, total_fees)
+    }
+}
+
+#[cfg(all(any(target_arch = "wasm32", feature = "export-abi"), not(feature = "views")))]
+impl BattleScoring {
+    /// Fail unless the caller is the owner.
+    fn only_owner(&self) -> Result<(), ScoringError> {
+        let caller = self.vm().msg_sender();
+        if caller != self.owner.get() {
+            return Err(ScoringError::NotOwner(NotOwner { caller }));
+        }
+        Ok(())
+    }
+
+    /// Set the owner and emit OwnershipTransferred.
+    fn store_owner(&mut self, new_owner: Address) {
+        let previous_owner = self.owner.get();
+        self.owner.set(new_owner);
+        self.vm().log(OwnershipTransferred {
+            previousOwner: previous_owner,
+            newOwner: new_owner,
+        });
+    }
+
+    /// Write a new registry entry (enabled) and emit DexRegistered.
+    fn store_new_dex(&mut self, dex_type: u8, name: String, weight_bps: U256) {
+        let mut dex = self.dexes.setter(U8::from(dex_type));
+        dex.name.set_str(&name);
+        dex.weight_bps.set(weight_bps);
+        dex.enabled.set(true);
+        dex.registered.set(true);
+        self.dex_types.push(U8::from(dex_type));
+
+        self.vm().log(DexRegistered {
+            dexType: dex_type,
+            name,
+            weightBps: weight_bps,
+        });
+    }
+
+    /// Write the range parameters and emit RangeParamsUpdated.
+    fn store_range_params(&mut self, threshold: U256, max_bonus: U256) {
+        self.range_threshold.set(threshold);
+        self.range_max_bonus.set(max_bonus);
+
+        self.vm().log(RangeParamsUpdated {
+            threshold,
+            maxBonus: max_bonus,
+        });
+    }
+
+    /// Write the live resolver bps limits and emit ResolverBpsLimitsUpdated.
+    fn store_resolver_bps_limits(&mut self, min_bps: U256, max_bps: U256) {
+        self.min_resolver_bps.set(min_bps);
+        self.max_resolver_bps.set(max_bps);
+
+        self.vm().log(ResolverBpsLimitsUpdated {
+            minBps: min_bps,
+            maxBps: max_bps,
+        });
+    }
+
+    /// Look up a published parameter set, failing for unknown versions.
+    fn param_set(&self, version: U256) -> Result<StorageGuard<'_, ParamSet>, ScoringError> {
+        usize::try_from(version)
+            .ok()
+            .and_then(|index| self.param_sets.getter(index))
+            .ok_or(ScoringError::UnknownParamSet(UnknownParamSet { version }))
+    }
+
+    /// Look up the weight of a DEX that is registered and enabled.
+    fn active_dex_weight(&self, dex_type: u8) -> Result<U256, ScoringError> {
+        let dex = self.dexes.getter(U8::from(dex_type));
+        active_dex_weight(dex_type, dex.registered.get(), dex.enabled.get(), dex.weight_bps.get())
+    }
+}
+
+// ============ Pure logic functions (testable without Stylus VM) ============
+
+/// Add, failing with `Overflow` instead of wrapping.
+fn checked_add(a: U256, b: U256) -> Result<U256, ScoringError> {
+    a.checked_add(b).ok_or(ScoringError::Overflow(Overflow {}))
+}
+
+/// Build an `UnknownDex` error.
+fn unknown_dex(dex_type: u8) -> ScoringError {
+    ScoringError::UnknownDex(UnknownDex { dexType: dex_type })
+}
+
+/// Calculate range score: (inRangeTime / totalTime) * 1e18, with tick tightness bonus.
+/// Uses the default threshold (100 ticks) and maximum bonus (20%).
+pub fn range_score(
+    in_range_time: U256,
+    total_time: U256,
+    tick_distance: U256,
+) -> Result<U256, ScoringError> {
+    range_score_with_params(
+        in_range_time,
+        total_time,
+        tick_distance,
+        U256::from(TIGHT_RANGE_THRESHOLD),
+        U256::from(TIGHT_RANGE_BONUS),
+    )
+}
+
+/// Calculate range score with an explicit tightness threshold and maximum bonus (1e18 = 100%).
+/// A threshold of zero disables the bonus.
+///
+/// Fails with `ZeroDuration` if total_time is zero and `InRangeExceedsTotal`
+/// if in_range_time is larger than total_time.
+pub fn range_score_with_params(
+    in_range_time: U256,
+    total_time: U256,
+    tick_distance: U256,
+    threshold: U256,
+    max_bonus: U256,
+) -> Result<U256, ScoringError> {
+    let (_, _, score) = range_score_steps(in_range_time, total_time, tick_distance, threshold, max_bonus)?;
+    Ok(score)
+}
+
+/// Range score as (baseScore, bonus, score), shared by `range_score_with_params`
+/// and `explain_range_score` so the breakdown always matches the real score.
+fn range_score_steps(
+    in_range_time: U256,
+    total_time: U256,
+    tick_distance: U256,
+    threshold: U256,
+    max_bonus: U256,
+) -> Result<(U256, U256, U256), ScoringError> {
+    if total_time.is_zero() {
+        return Err(ScoringError::ZeroDuration(ZeroDuration {}));
+    }
+    if in_range_time > total_time {
+        return Err(ScoringError::InRangeExceedsTotal(InRangeExceedsTotal {
+            inRangeTime: in_range_time,
+            totalTime: total_time,
+        }));
+    }
+
+    let decimals = U256::from(SCORE_DECIMALS);
+
+    // Base score: (inRangeTime * 1e18) / totalTime
+    let base_score = mul_div_down(in_range_time, decimals, total_time)?;
+
+    // Tick distance bonus: tighter ranges get up to max_bonus
+    let bonus = if tick_distance < threshold {
+        // Linear bonus: bonus = maxBonus * (threshold - tickDistance) / threshold
+        mul_div_down(max_bonus, threshold - tick_distance, threshold)?
+    } else {
+        U256::ZERO
+    };
+
+    // Final score = baseScore + (baseScore * bonus / 1e18)
+    let score = checked_add(base_score, mul_div_down(base_score, bonus, decimals)?)?;
+    Ok((base_score, bonus, score))
+}
+
+/// Position liquidity as a share of the pool's active liquidity, in 1e18.
+///
+/// An empty pool gives a zero share. A position holding more liquidity than is
+/// active cannot be part of it, so it is out of range and also gets a zero share.
+pub fn liquidity_share(position_liquidity: u128, pool_liquidity: u128) -> Result<U256, ScoringError> {
+    if pool_liquidity == 0 || position_liquidity > pool_liquidity {
+        return Ok(U256::ZERO);
+    }
+    mul_div_down(
+        U256::from(position_liquidity),
+        U256::from(SCORE_DECIMALS),
+        U256::from(pool_liquidity),
+    )
+    .map_err(ScoringError::from)
+}
+
+/// Range score weighted by liquidity density, in 1e18.
+///
+/// score = (inRangeTime / totalTime) * min(share, cap) / cap, where share is the
+/// position's fraction of active liquidity and cap is `share_cap_bps`. For the same
+/// capital a narrower range mints more liquidity and so a larger share, which makes
+/// this reward effective concentration instead of width alone: a dust position in a
+/// one-tick range scores next to nothing, and no position scores above 1e18 however
+/// much liquidity it holds.
+pub fn density_range_score(
+    in_range_time: U256,
+    total_time: U256,
+    position_liquidity: u128,
+    pool_liquidity: u128,
+    share_cap_bps: U256,
+) -> Result<U256, ScoringError> {
+    if share_cap_bps.is_zero() || share_cap_bps > U256::from(MAX_BPS) {
+        return Err(ScoringError::InvalidLiquidityShareCap(InvalidLiquidityShareCap {
+            capBps: share_cap_bps,
+        }));
+    }
+    // Zero threshold disables the width bonus, leaving the plain in-range ratio
+    let base_score = range_score_with_params(in_range_time, total_time, U256::ZERO, U256::ZERO, U256::ZERO)?;
+
+    let decimals = U256::from(SCORE_DECIMALS);
+    let cap = share_cap_bps * decimals / U256::from(MAX_BPS);
+    let share = liquidity_share(position_liquidity, pool_liquidity)?.min(cap);
+    Ok(mul_div_down(base_score, share, cap)?)
+}
+
+/// Step-by-step breakdown of a score, for explaining a result to players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    /// Score before any bonus: the in-range ratio (1e18 = 100%) or the fee yield rate
+    pub base_score: U256,
+    /// Tightness bonus applied to the base score (1e18 = +100%); always zero for fee scores
+    pub bonus: U256,
+    /// Score after the bonus, before DEX weighting (what `range_score` / `fee_score` return)
+    pub raw_score: U256,
+    /// DEX weight applied in bps (10000 = 1.0x)
+    pub dex_weight_bps: U256,
+    /// `raw_score` after DEX weighting, as used to pick the winner
+    pub final_score: U256,
+}
+
+/// Break a range score into its steps. `raw_score` equals `range_score_with_params`
+/// and `final_score` equals `normalize_cross_dex` of it.
+pub fn explain_range_score(
+    in_range_time: U256,
+    total_time: U256,
+    tick_distance: U256,
+    threshold: U256,
+    max_bonus: U256,
+    dex_weight_bps: U256,
+) -> Result<ScoreBreakdown, ScoringError> {
+    let (base_score, bonus, raw_score) =
+        range_score_steps(in_range_time, total_time, tick_distance, threshold, max_bonus)?;
+    Ok(ScoreBreakdown {
+        base_score,
+        bonus,
+        raw_score,
+        dex_weight_bps,
+        final_score: normalize_cross_dex(raw_score, dex_weight_bps)?,
+    })
+}
+
+/// Break a fee score into its steps. Fee scores have no bonus, so `base_score`
+/// and `raw_score` both equal `fee_score`.
+pub fn explain_fee_score(
+    fees_usd: U256,
+    lp_value_usd: U256,
+    duration: U256,
+    dex_weight_bps: U256,
+) -> Result<ScoreBreakdown, ScoringError> {
+    let raw_score = fee_score(fees_usd, lp_value_usd, duration)?;
+    Ok(ScoreBreakdown {
+        base_score: raw_score,
+        bonus: U256::ZERO,
+        raw_score,
+        dex_weight_bps,
+        final_score: normalize_cross_dex(raw_score, dex_weight_bps)?,
+    })
+}
+
+/// Resolve a DEX weight pinned in a parameter set. Zero means the DEX was
+/// unregistered or disabled when the set was published.
+pub fn pinned_dex_weight(dex_type: u8, weight_bps: U256) -> Result<U256, ScoringError> {
+    if weight_bps.is_zero() {
+        return Err(ScoringError::DexNotInParamSet(DexNotInParamSet { dexType: dex_type }));
+    }
+    Ok(weight_bps)
+}
+
+/// Check that resolver bps limits satisfy min <= max <= MAX_BPS.
+pub fn is_valid_resolver_bps_limits(min_bps: U256, max_bps: U256) -> bool {
+    min_bps <= max_bps && max_bps <= U256::from(MAX_BPS)
+}
+
+/// Check that a resolver bps value lies within [min_bps, max_bps].
+pub fn resolver_bps_within_limits(resolver_bps: U256, min_bps: U256, max_bps: U256) -> bool {
+    resolver_bps >= min_bps && resolver_bps <= max_bps
+}
+
+/// Check that range parameters lie within [0, MAX_RANGE_THRESHOLD] and [0, MAX_RANGE_BONUS].
+pub fn is_valid_range_params(threshold: U256, max_bonus: U256) -> bool {
+    threshold <= U256::from(MAX_RANGE_THRESHOLD) && max_bonus <= U256::from(MAX_RANGE_BONUS)
+}
+
+/// Calculate fee yield rate: (feesUSD * 1e18) / (lpValueUSD * duration)
+///
+/// Computed as `mulDiv(feesUSD, 1e18, lpValueUSD) / duration`, which floors to the
+/// same value without ever forming the `lpValueUSD * duration` product.
+///
+/// Fails with `ZeroLpValue` or `ZeroDuration` instead of returning a zero
+/// score, so callers can tell bad data apart from a position that earned nothing.
+pub fn fee_score(fees_usd: U256, lp_value_usd: U256, duration: U256) -> Result<U256, ScoringError> {
+    if lp_value_usd.is_zero() {
+        return Err(ScoringError::ZeroLpValue(ZeroLpValue {}));
+    }
+    if duration.is_zero() {
+        return Err(ScoringError::ZeroDuration(ZeroDuration {}));
+    }
+    let decimals = U256::from(SCORE_DECIMALS);
+    Ok(mul_div_down(fees_usd, decimals, lp_value_usd)? / duration)
+}
+
+/// Fees collected in one token, with the price used to value them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenFees {
+    /// Raw token amount in the token's smallest unit
+    pub amount: U256,
+    /// Token decimals (e.g. 18 for WETH, 6 for USDC)
+    pub decimals: u8,
+    /// USD price of one whole token, scaled by 10^price_decimals
+    pub price: U256,
+    /// Decimals of `price` (e.g. 8 for Chainlink USD feeds)
+    pub price_decimals: u8,
+}
+
+/// 10^exponent; the caller keeps exponent <= 2 * MAX_TOKEN_DECIMALS so it cannot overflow.
+fn pow10(exponent: u8) -> U256 {
+    U256::from(10u64).pow(U256::from(exponent))
+}
+
+/// Value a raw token amount in 8-decimal USD, rounded down:
+/// `amount * price / 10^(decimals + price_decimals - 8)`.
+/// Fails with `InvalidDecimals` above MAX_TOKEN_DECIMALS and `Overflow` if the value
+/// does not fit in 256 bits.
+pub fn token_usd_value(token: &TokenFees) -> Result<U256, ScoringError> {
+    for decimals in [token.decimals, token.price_decimals] {
+        if decimals > MAX_TOKEN_DECIMALS {
+            return Err(ScoringError::InvalidDecimals(InvalidDecimals { decimals }));
+        }
+    }
+    let scale = token.decimals + token.price_decimals;
+    if scale >= USD_DECIMALS {
+        Ok(mul_div_down(token.amount, token.price, pow10(scale - USD_DECIMALS))?)
+    } else {
+        // Fewer combined decimals than USD: scale up instead of dividing
+        token
+            .amount
+            .checked_mul(token.price)
+            .and_then(|value| value.checked_mul(pow10(USD_DECIMALS - scale)))
+            .ok_or(ScoringError::Overflow(Overflow {}))
+    }
+}
+
+/// Calculate a fee score from raw token amounts: both tokens are valued with
+/// `token_usd_value`, summed and passed to `fee_score`. `lp_value_usd` is 8-decimal USD.
+pub fn fee_score_from_tokens(
+    token0: &TokenFees,
+    token1: &TokenFees,
+    lp_value_usd: U256,
+    duration: U256,
+) -> Result<U256, ScoringError> {
+    let fees_usd = checked_add(token_usd_value(token0)?, token_usd_value(token1)?)?;
+    fee_score(fees_usd, lp_value_usd, duration)
+}
+
+/// Fees with the position's share of pool donations removed.
+///
+/// A V4 `donate()` of `amount` adds `amount * 2^128 / liquidity` to the fee growth of
+/// every position in range at that moment, so the hook records that growth per unit of
+/// liquidity at each donation, sums it per locked range and reports it from the moment
+/// the position was locked. The position received
+/// `donation_growth_x128 * position_liquidity / 2^128` of the donations, whatever the
+/// pool's liquidity is later; subtracting it leaves only fees earned from swaps, so a
+/// player who donates to their own pool pays the full donation but gains no score from
+/// it. The share is rounded up, the result saturates at zero, and `fees` is in raw
+/// amounts of the donated token.
+pub fn donation_adjusted_fees(
+    fees: U256,
+    donation_growth_x128: U256,
+    position_liquidity: u128,
+) -> Result<U256, ScoringError> {
+    let donation_share = mul_div_up(
+        donation_growth_x128,
+        U256::from(position_liquidity),
+        U256::from(1u64) << 128,
+    )?;
+    Ok(fees.saturating_sub(donation_share))
+}
+
+/// Annualized fee yield: feesUSD / lpValueUSD * (365 days / duration), in 1e18 (1e18 = 100% APR).
+/// Fails like `fee_score`.
+pub fn fee_apr(fees_usd: U256, lp_value_usd: U256, duration: U256) -> Result<U256, ScoringError> {
+    if lp_value_usd.is_zero() {
+        return Err(ScoringError::ZeroLpValue(ZeroLpValue {}));
+    }
+    if duration.is_zero() {
+        return Err(ScoringError::ZeroDuration(ZeroDuration {}));
+    }
+    let yield_ratio = mul_div_down(fees_usd, U256::from(SCORE_DECIMALS), lp_value_usd)?;
+    Ok(mul_div_down(yield_ratio, U256::from(SECONDS_PER_YEAR), duration)?)
+}
+
+/// "Beat the pool" fee score: the position's fee APR relative to the pool's, in 1e18.
+///
+/// A 1% pool pays every LP in it more than a 0.05% pool does, so comparing raw yields
+/// across pools mostly compares fee tiers. Dividing by the pool-wide fee APR over the
+/// same window measures how much better than the average LP each player did, which
+/// stays fair across fee tiers, dynamic-fee pools and DEXes. `pool_fee_apr` is in 1e18
+/// and is measured on the player's own pool, so the tier (or the average dynamic fee)
+/// is already in it and needs no separate input. There is no fallback baseline: a
+/// missing (zero) pool APR fails with `ZeroPoolFeeApr`.
+pub fn pool_relative_fee_score(
+    fees_usd: U256,
+    lp_value_usd: U256,
+    duration: U256,
+    pool_fee_apr: U256,
+) -> Result<U256, ScoringError> {
+    if pool_fee_apr.is_zero() {
+        return Err(ScoringError::ZeroPoolFeeApr(ZeroPoolFeeApr {}));
+    }
+    let apr = fee_apr(fees_usd, lp_value_usd, duration)?;
+    Ok(mul_div_down(apr, U256::from(SCORE_DECIMALS), pool_fee_apr)?)
+}
+
+/// Fees owed to `liquidity` for both tokens between the start and end
+/// `feeGrowthInside0/1X128` of its range, as (fees0, fees1) in raw token amounts.
+/// Each growth delta wraps modulo 2^256 like the on-chain accumulators.
+pub fn position_fees_owed(
+    start_fee_growth0_x128: U256,
+    start_fee_growth1_x128: U256,
+    end_fee_growth0_x128: U256,
+    end_fee_growth1_x128: U256,
+    liquidity: u128,
+) -> Result<(U256, U256), ScoringError> {
+    Ok((
+        fees_owed(start_fee_growth0_x128, end_fee_growth0_x128, liquidity)?,
+        fees_owed(start_fee_growth1_x128, end_fee_growth1_x128, liquidity)?,
+    ))
+}
+
+/// Inputs to `net_pnl_score` for one concentrated liquidity position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetPnlInput {
+    /// Pool sqrt price (Q64.96) when the battle started
+    pub entry_sqrt_price_x96: U256,
+    /// Pool sqrt price (Q64.96) when the battle ended
+    pub exit_sqrt_price_x96: U256,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    /// Fees collected during the battle, in raw token amounts
+    pub fees0: U256,
+    pub fees1: U256,
+}
+
+/// Check tick bounds and return their sqrt prices (lower, upper).
+fn tick_range_sqrt_prices(tick_lower: i32, tick_upper: i32) -> Result<(U256, U256), ScoringError> {
+    if tick_lower >= tick_upper {
+        return Err(ScoringError::InvalidTickRange(InvalidTickRange {
+            tickLower: tick_lower,
+            tickUpper: tick_upper,
+        }));
+    }
+    Ok((sqrt_ratio_at_tick(tick_lower)?, sqrt_ratio_at_tick(tick_upper)?))
+}
+
+/// Token amounts (amount0, amount1) held by `liquidity` in [tick_lower, tick_upper)
+/// at `sqrt_price_x96`, rounded down. Fails with `InvalidTickRange` or
+/// `TickOutOfRange` for bad bounds.
+pub fn position_amounts(
+    liquidity: u128,
+    tick_lower: i32,
+    tick_upper: i32,
+    sqrt_price_x96: U256,
+) -> Result<(U256, U256), ScoringError> {
+    let (sqrt_lower, sqrt_upper) = tick_range_sqrt_prices(tick_lower, tick_upper)?;
+    Ok(amounts_for_liquidity(sqrt_price_x96, sqrt_lower, sqrt_upper, liquidity)?)
+}
+
+/// Token amounts and USD value of a concentrated liquidity position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionValue {
+    pub amount0: U256,
+    pub amount1: U256,
+    /// 8-decimal USD
+    pub value_usd: U256,
+}
+
+/// Value a position at `sqrt_price_x96`: `position_amounts`, then each token priced
+/// with `token_usd_value` using 8-decimal USD prices per whole token, summed.
+/// Both legs round down, so the value never overstates the position.
+#[allow(clippy::too_many_arguments)]
+pub fn position_value_usd(
+    liquidity: u128,
+    tick_lower: i32,
+    tick_upper: i32,
+    sqrt_price_x96: U256,
+    price0: U256,
+    price1: U256,
+    decimals0: u8,
+    decimals1: u8,
+) -> Result<PositionValue, ScoringError> {
+    let (amount0, amount1) = position_amounts(liquidity, tick_lower, tick_upper, sqrt_price_x96)?;
+    let value0 = token_usd_value(&TokenFees {
+        amount: amount0,
+        decimals: decimals0,
+        price: price0,
+        price_decimals: USD_DECIMALS,
+    })?;
+    let value1 = token_usd_value(&TokenFees {
+        amount: amount1,
+        decimals: decimals1,
+        price: price1,
+        price_decimals: USD_DECIMALS,
+    })?;
+    Ok(PositionValue {
+        amount0,
+        amount1,
+        value_usd: checked_add(value0, value1)?,
+    })
+}
+
+/// Check that `a` and `b` differ by at most `bps` of the larger value.
+/// Two zero values are always within tolerance.
+pub fn value_within_tolerance(a: U256, b: U256, bps: U256) -> Result<bool, ScoringError> {
+    let max_value = a.max(b);
+    if max_value.is_zero() {
+        return Ok(true);
+    }
+    let tolerance = mul_div_down(max_value, bps, U256::from(MAX_BPS))?;
+    Ok(a.abs_diff(b) <= tolerance)
+}
+
+/// Pool tick observed at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickCheckpoint {
+    pub timestamp: u64,
+    pub tick: i32,
+}
+
+/// Battle time window in unix seconds, `start` inclusive and `end` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BattleWindow {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Exact seconds in range within `window`, from tick checkpoints.
+///
+/// Each checkpoint's tick holds until the next checkpoint (or the window end), and
+/// the position is in range while `tick_lower <= tick < tick_upper`. Checkpoints must
+/// be strictly increasing in time, the first must be at or before `window.start`, and
+/// no stretch inside the window may go longer than `max_gap` seconds without a
+/// checkpoint. Checkpoints at or after `window.end` are ignored.
+pub fn in_range_time_from_checkpoints(
+    checkpoints: &[TickCheckpoint],
+    tick_lower: i32,
+    tick_upper: i32,
+    window: BattleWindow,
+    max_gap: u64,
+) -> Result<u64, ScoringError> {
+    if window.start >= window.end {
+        return Err(ScoringError::InvalidBattleWindow(InvalidBattleWindow {
+            start: U256::from(window.start),
+            end: U256::from(window.end),
+        }));
+    }
+    if tick_lower >= tick_upper {
+        return Err(ScoringError::InvalidTickRange(InvalidTickRange {
+            tickLower: tick_lower,
+            tickUpper: tick_upper,
+        }));
+    }
+    match checkpoints.first() {
+        Some(first) if first.timestamp <= window.start => {}
+        first => {
+            return Err(ScoringError::CheckpointsDoNotCover(CheckpointsDoNotCover {
+                start: U256::from(window.start),
+                firstTimestamp: U256::from(first.map_or(u64::MAX, |checkpoint| checkpoint.timestamp)),
+            }));
+        }
+    }
+    if let Some(index) = checkpoints.windows(2).position(|pair| pair[1].timestamp <= pair[0].timestamp) {
+        return Err(ScoringError::CheckpointsOutOfOrder(CheckpointsOutOfOrder {
+            index: U256::from(index + 1),
+        }));
+    }
+
+    let mut in_range = 0u64;
+    for (index, checkpoint) in checkpoints.iter().enumerate() {
+        let next = checkpoints.get(index + 1).map_or(window.end, |next| next.timestamp);
+        // Clamp the stretch this checkpoint covers to the window
+        let from = checkpoint.timestamp.max(window.start);
+        let to = next.min(window.end);
+        if to <= from {
+            continue;
+        }
+        if to - from > max_gap {
+            return Err(ScoringError::CheckpointGapTooLarge(CheckpointGapTooLarge {
+                from: U256::from(from),
+                to: U256::from(to),
+            }));
+        }
+        if tick_lower <= checkpoint.tick && checkpoint.tick < tick_upper {
+            in_range += to - from;
+        }
+    }
+    Ok(in_range)
+}
+
+/// Uniswap-style oracle values for one position at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CumulativeSnapshot {
+    /// Block timestamp, truncated to uint32 like oracle observations
+    pub timestamp: u32,
+    /// Pool tick cumulative (int56; must lie in [-2^55, 2^55))
+    pub tick_cumulative: i64,
+    /// Seconds the pool tick spent inside the position range (uint32
+    /// `secondsInside` from `snapshotCumulativesInside`)
+    pub seconds_inside: u32,
+}
+
+/// In-range time and mean tick between two snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotRangeStats {
+    /// Seconds between the snapshots
+    pub elapsed: u32,
+    /// Seconds the position was in range
+    pub in_range_time: u64,
+    /// Time-weighted mean tick, rounded toward negative infinity
+    pub mean_tick: i32,
+}
+
+/// Bit width of oracle tick cumulatives (int56)
+const TICK_CUMULATIVE_BITS: u32 = 56;
+
+/// `end - start` for int56 tick cumulatives, wrapping modulo 2^56 like Solidity's
+/// unchecked int56 arithmetic, and sign-extended back to i64.
+fn tick_cumulative_delta(start: i64, end: i64) -> Result<i64, ScoringError> {
+    let bound = 1i64 << (TICK_CUMULATIVE_BITS - 1);
+    if !(-bound..bound).contains(&start) || !(-bound..bound).contains(&end) {
+        return Err(ScoringError::CumulativeOutOfRange(CumulativeOutOfRange {}));
+    }
+    let shift = 64 - TICK_CUMULATIVE_BITS;
+    Ok((end.wrapping_sub(start) << shift) >> shift)
+}
+
+/// In-range time and time-weighted mean tick from two oracle snapshots.
+///
+/// In-range seconds = Δ(secondsInside), the time the pool tick spent inside the
+/// position range regardless of how much other liquidity was active. Mean tick =
+/// Δ(tickCumulative) / elapsed, rounded toward negative infinity as
+/// `OracleLibrary.consult` does. Timestamps and seconds inside wrap modulo 2^32 and
+/// tick cumulatives modulo 2^56.
+/// Fails with `ZeroDuration` if both snapshots share a timestamp,
+/// `InRangeExceedsTotal` if the in-range time is longer than the window and
+/// `TickOutOfRange` if the mean tick is outside [MIN_TICK, MAX_TICK].
+pub fn range_stats_from_snapshots(
+    start: &CumulativeSnapshot,
+    end: &CumulativeSnapshot,
+) -> Result<SnapshotRangeStats, ScoringError> {
+    let elapsed = end.timestamp.wrapping_sub(start.timestamp);
+    if elapsed == 0 {
+        return Err(ScoringError::ZeroDuration(ZeroDuration {}));
+    }
+
+    let in_range = end.seconds_inside.wrapping_sub(start.seconds_inside);
+    if in_range > elapsed {
+        return Err(ScoringError::InRangeExceedsTotal(InRangeExceedsTotal {
+            inRangeTime: U256::from(in_range),
+            totalTime: U256::from(elapsed),
+        }));
+    }
+
+    let tick_delta = tick_cumulative_delta(start.tick_cumulative, end.tick_cumulative)?;
+    let mean_tick = i32::try_from(tick_delta.div_euclid(i64::from(elapsed)))
+        .ok()
+        .filter(|tick| (MIN_TICK..=MAX_TICK).contains(tick))
+        .ok_or(ScoringError::TickOutOfRange(TickOutOfRange {}))?;
+
+    Ok(SnapshotRangeStats {
+        elapsed,
+        in_range_time: u64::from(in_range),
+        mean_tick,
+    })
+}
+
+/// Impermanent-loss-aware net PnL score, in 1e18.
+///
+/// Both sides are valued in token1 at the exit price:
+///   HODL = the position's token amounts at entry, held unchanged
+///   LP   = the position's token amounts at exit + collected fees
+/// Score = LP * 1e18 / HODL, so 1e18 means the position exactly matched holding,
+/// above 1e18 fees outran impermanent loss and below it they did not.
+/// Fails with `ZeroLpValue` if the position held nothing at entry.
+pub fn net_pnl_score(input: &NetPnlInput) -> Result<U256, ScoringError> {
+    let (sqrt_lower, sqrt_upper) = tick_range_sqrt_prices(input.tick_lower, input.tick_upper)?;
+    let (entry_price, exit_price) = (input.entry_sqrt_price_x96, input.exit_sqrt_price_x96);
+
+    let (entry0, entry1) = amounts_for_liquidity(entry_price, sqrt_lower, sqrt_upper, input.liquidity)?;
+    let (exit0, exit1) = amounts_for_liquidity(exit_price, sqrt_lower, sqrt_upper, input.liquidity)?;
+
+    let hodl_value = checked_add(quote_token0_in_token1(entry0, exit_price)?, entry1)?;
+    if hodl_value.is_zero() {
+        return Err(ScoringError::ZeroLpValue(ZeroLpValue {}));
+    }
+    let lp_value = checked_add(quote_token0_in_token1(exit0, exit_price)?, exit1)?;
+    let fees_value = checked_add(quote_token0_in_token1(input.fees0, exit_price)?, input.fees1)?;
+
+    Ok(mul_div_down(checked_add(lp_value, fees_value)?, U256::from(SCORE_DECIMALS), hodl_value)?)
+}
+
+/// Check that a hybrid range weight is at most MAX_BPS and the fee ceiling is non-zero.
+pub fn is_valid_hybrid_params(range_weight_bps: U256, fee_ceiling: U256) -> bool {
+    range_weight_bps <= U256::from(MAX_BPS) && !fee_ceiling.is_zero()
+}
+
+/// Rescale a range score to 0..=1e18 by dividing out the largest possible bonus:
+/// a full-time position at the tightest range (1e18 + max_bonus) maps to 1e18.
+pub fn rescale_range_score(score: U256, max_bonus: U256) -> Result<U256, ScoringError> {
+    let decimals = U256::from(SCORE_DECIMALS);
+    let best = checked_add(decimals, max_bonus)?;
+    Ok(mul_div_down(score, decimals, best)?.min(decimals))
+}
+
+/// Rescale a fee score to 0..=1e18 linearly against `fee_ceiling`; scores at or
+/// above the ceiling map to 1e18.
+pub fn rescale_fee_score(score: U256, fee_ceiling: U256) -> Result<U256, ScoringError> {
+    Ok(mul_div_down(score.min(fee_ceiling), U256::from(SCORE_DECIMALS), fee_ceiling)?)
+}
+
+/// Blend a range score and a fee score into a hybrid score in 0..=1e18:
+/// `(range' * w + fee' * (10000 - w)) / 10000`, where range' and fee' are the
+/// rescaled scores and w is `range_weight_bps`. w = 10000 is pure range, w = 0 pure fees.
+pub fn hybrid_score(
+    range_score: U256,
+    fee_score: U256,
+    max_bonus: U256,
+    fee_ceiling: U256,
+    range_weight_bps: U256,
+) -> Result<U256, ScoringError> {
+    if !is_valid_hybrid_params(range_weight_bps, fee_ceiling) {
+        return Err(ScoringError::InvalidHybridParams(InvalidHybridParams {
+            rangeWeightBps: range_weight_bps,
+            feeCeiling: fee_ceiling,
+        }));
+    }
+    let max_bps = U256::from(MAX_BPS);
+    let range_part = mul_div_down(rescale_range_score(range_score, max_bonus)?, range_weight_bps, max_bps)?;
+    let fee_weight_bps = max_bps - range_weight_bps;
+    let fee_part = mul_div_down(rescale_fee_score(fee_score, fee_ceiling)?, fee_weight_bps, max_bps)?;
+    checked_add(range_part, fee_part)
+}
+
+/// Determine winner: 1 = player A, 2 = player B. Tie goes to A.
+pub fn winner(score_a: U256, score_b: U256) -> u8 {
+    if score_a >= score_b { 1 } else { 2 }
+}
+
+/// Determine outcome: 0 = draw, 1 = player A, 2 = player B.
+/// Scores whose difference is at most `tolerance_bps` of the higher score are a draw,
+/// so two zero scores are always a draw.
+pub fn outcome(score_a: U256, score_b: U256, tolerance_bps: U256) -> u8 {
+    let (high, low) = if score_a >= score_b {
+        (score_a, score_b)
+    } else {
+        (score_b, score_a)
+    };
+
+    // |a - b| / high <= tolerance / MAX_BPS, rearranged to avoid division and
+    // compared at 512 bits so large scores cannot saturate into a false draw
+    let diff_scaled: U512 = (high - low).widening_mul(U256::from(MAX_BPS));
+    let allowed: U512 = high.widening_mul(tolerance_bps);
+    if diff_scaled <= allowed {
+        OUTCOME_DRAW
+    } else if score_a > score_b {
+        OUTCOME_PLAYER_A
+    } else {
+        OUTCOME_PLAYER_B
+    }
+}
+
+/// Check that a draw tolerance does not exceed MAX_DRAW_TOLERANCE_BPS.
+pub fn is_valid_draw_tolerance(tolerance_bps: U256) -> bool {
+    tolerance_bps <= U256::from(MAX_DRAW_TOLERANCE_BPS)
+}
+
+/// Secondary metrics used to break a tie between two players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieBreakMetrics {
+    /// Position range width in ticks (tickUpper - tickLower)
+    pub tick_width: U256,
+    /// USD value at stake (8 decimals)
+    pub value_usd: U256,
+    /// Timestamp at which the player joined the battle
+    pub joined_at: U256,
+}
+
+/// Break a tie: returns (winner, rule) where winner is 1 = player A, 2 = player B
+/// and rule is the TIEBREAK_* constant that decided it.
+///
+/// Rules are tried in order:
+///   1. Narrower tick width wins (more concentrated liquidity)
+///   2. Lower USD value at stake wins (better capital efficiency)
+///   3. Earlier join timestamp wins
+///   4. Coin flip: lowest bit of keccak256(seed), 0 = A, 1 = B
+pub fn break_tie(a: &TieBreakMetrics, b: &TieBreakMetrics, seed: U256) -> (u8, u8) {
+    let rules = [
+        (a.tick_width, b.tick_width, TIEBREAK_TICK_WIDTH),
+        (a.value_usd, b.value_usd, TIEBREAK_USD_VALUE),
+        (a.joined_at, b.joined_at, TIEBREAK_JOIN_TIME),
+    ];
+    for (metric_a, metric_b, rule) in rules {
+        if metric_a < metric_b {
+            return (OUTCOME_PLAYER_A, rule);
+        }
+        if metric_b < metric_a {
+            return (OUTCOME_PLAYER_B, rule);
+        }
+    }
+
+    let hash = keccak256(seed.to_be_bytes::<32>());
+    if hash[31] & 1 == 0 {
+        (OUTCOME_PLAYER_A, TIEBREAK_COIN_FLIP)
+    } else {
+        (OUTCOME_PLAYER_B, TIEBREAK_COIN_FLIP)
+    }
+}
+
+/// Calculate rewards: (winnerAmount, resolverAmount) from total fees.
+pub fn rewards(total_fees: U256, resolver_bps: U256) -> Result<(U256, U256), ScoringError> {
+    let max_bps = U256::from(MAX_BPS);
+    if resolver_bps >= max_bps {
+        return Ok((U256::ZERO, total_fees));
+    }
+    let resolver_amount = mul_div_down(total_fees, resolver_bps, max_bps)?;
+    let winner_amount = total_fees - resolver_amount;
+    Ok((winner_amount, resolver_amount))
+}
+
+/// Calculate a three-way split: (winnerAmount, resolverAmount, protocolAmount).
+/// Resolver and protocol cuts round down; the rounding dust goes to the winner,
+/// so the three amounts always sum to `total_fees`.
+/// Rejects `resolver_bps + protocol_bps > max_combined_bps` and `max_combined_bps > MAX_BPS`.
+pub fn rewards_v2(
+    total_fees: U256,
+    resolver_bps: U256,
+    protocol_bps: U256,
+    max_combined_bps: U256,
+) -> Result<(U256, U256, U256), ScoringError> {
+    let max_bps = U256::from(MAX_BPS);
+    let in_range = max_combined_bps <= max_bps
+        && matches!(resolver_bps.checked_add(protocol_bps), Some(combined) if combined <= max_combined_bps);
+    if !in_range {
+        return Err(ScoringError::RewardBpsOutOfRange(RewardBpsOutOfRange {
+            resolverBps: resolver_bps,
+            protocolBps: protocol_bps,
+            maxCombinedBps: max_combined_bps,
+        }));
+    }
+
+    let resolver_amount = mul_div_down(total_fees, resolver_bps, max_bps)?;
+    let protocol_amount = mul_div_down(total_fees, protocol_bps, max_bps)?;
+    let winner_amount = total_fees - resolver_amount - protocol_amount;
+    Ok((winner_amount, resolver_amount, protocol_amount))
+}
+
+/// Per-party reward amounts in both pool tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairRewards {
+    pub winner0: U256,
+    pub winner1: U256,
+    pub resolver0: U256,
+    pub resolver1: U256,
+    pub protocol0: U256,
+    pub protocol1: U256,
+}
+
+/// Split fees collected in token0 and token1 between winner, resolver and protocol.
+/// Each token is split independently with `rewards_v2`, so rounding dust in each
+/// token goes to the winner and every token's total is conserved.
+pub fn pair_rewards(
+    fees0: U256,
+    fees1: U256,
+    resolver_bps: U256,
+    protocol_bps: U256,
+) -> Result<PairRewards, ScoringError> {
+    let max_bps = U256::from(MAX_BPS);
+    let (winner0, resolver0, protocol0) = rewards_v2(fees0, resolver_bps, protocol_bps, max_bps)?;
+    let (winner1, resolver1, protocol1) = rewards_v2(fees1, resolver_bps, protocol_bps, max_bps)?;
+    Ok(PairRewards {
+        winner0,
+        winner1,
+        resolver0,
+        resolver1,
+        protocol0,
+        protocol1,
+    })
+}
+
+/// Calculate proportional rewards: (playerAAmount, playerBAmount).
+/// Each player gets `total * score / (score_a + score_b)`, rounded down; the
+/// leftover unit goes to the higher scorer (player A on equal scores) so the
+/// two amounts always sum to `total_fees`. Two zero scores split evenly.
+/// The score total and the products are taken at 512 bits, so any pair of
+/// scores is accepted.
+pub fn proportional_rewards(
+    score_a: U256,
+    score_b: U256,
+    total_fees: U256,
+) -> Result<(U256, U256), ScoringError> {
+    let (weight_a, weight_b) = if score_a.is_zero() && score_b.is_zero() {
+        (U256::from(1u64), U256::from(1u64))
+    } else {
+        (score_a, score_b)
+    };
+
+    let total_score = U512::from(weight_a) + U512::from(weight_b);
+    // weight <= total_score, so each share fits back into 256 bits.
+    let share = |weight: U256| (total_fees.widening_mul(weight) / total_score).to::<U256>();
+    let amount_a = share(weight_a);
+    let amount_b = share(weight_b);
+    let dust = total_fees - amount_a - amount_b;
+
+    if score_a >= score_b {
+        Ok((amount_a + dust, amount_b))
+    } else {
+        Ok((amount_a, amount_b + dust))
+    }
+}
+
+/// Calculate draw rewards: (playerAAmount, playerBAmount, resolverAmount).
+/// Each player gets half of what remains after the resolver cut; an odd
+/// leftover unit goes to the resolver so the split stays symmetric.
+pub fn draw_rewards(total_fees: U256, resolver_bps: U256) -> Result<(U256, U256, U256), ScoringError> {
+    let (pot, resolver_amount) = rewards(total_fees, resolver_bps)?;
+    let half = pot / U256::from(2u64);
+    let dust = pot - half - half;
+    Ok((half, half, resolver_amount + dust))
+}
+
+/// Placement of N ranked players.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranking {
+    /// Player indices from best to worst; equal scores keep their input order
+    pub order: Vec<u8>,
+    /// 1-based place of each player by input index; a draw group shares the
+    /// place of its best player and the next group skips ahead (1, 1, 3)
+    pub placements: Vec<u8>,
+}
+
+/// Reject player counts outside [MIN_RANKED_PLAYERS, MAX_RANKED_PLAYERS].
+fn check_player_count(count: usize) -> Result<(), ScoringError> {
+    if !(MIN_RANKED_PLAYERS..=MAX_RANKED_PLAYERS).contains(&count) {
+        return Err(ScoringError::InvalidPlayerCount(InvalidPlayerCount { count: U256::from(count) }));
+    }
+    Ok(())
+}
+
+/// Rank players by score, highest first.
+/// The sort is stable, so equal scores keep their input order. A player whose
+/// score is within `tolerance_bps` of the best score in the current group (see
+/// `outcome`) joins that group and shares its placement.
+pub fn rank(scores: &[U256], tolerance_bps: U256) -> Result<Ranking, ScoringError> {
+    check_player_count(scores.len())?;
+
+    let mut order: Vec<u8> = (0..scores.len() as u8).collect();
+    order.sort_by(|&i, &j| scores[j as usize].cmp(&scores[i as usize]));
+
+    let mut placements = vec![0u8; scores.len()];
+    let mut leader = order[0] as usize;
+    for (position, &player) in order.iter().enumerate() {
+        let player = player as usize;
+        if position == 0 || outcome(scores[leader], scores[player], tolerance_bps) != OUTCOME_DRAW {
+            leader = player;
+            placements[player] = position as u8 + 1;
+        } else {
+            placements[player] = placements[leader];
+        }
+    }
+    Ok(Ranking { order, placements })
+}
+
+/// Check that placements form a ranking where each group of n players sharing
+/// place p is followed by place p + n (1, 1, 3 or 1, 2, 2, 4).
+pub fn is_valid_placements(placements: &[u8]) -> bool {
+    let mut sorted = placements.to_vec();
+    sorted.sort_unstable();
+    sorted
+        .iter()
+        .enumerate()
+        .all(|(j, &place)| place as usize == j + 1 || (j > 0 && place == sorted[j - 1]))
+}
+
+/// Pay out `total_fees` by placement, returning one amount per player (by input index).
+/// Place k+1 is worth `payout_curve_bps[k]` (zero past the end of the curve). A draw
+/// group sharing place p with n players splits the value of places p..p+n-1 evenly.
+/// Rounding dust goes to the first player in 1st place, so amounts sum to `total_fees`.
+pub fn ranked_rewards(
+    total_fees: U256,
+    payout_curve_bps: &[U256],
+    placements: &[u8],
+) -> Result<Vec<U256>, ScoringError> {
+    check_player_count(placements.len())?;
+    if !is_valid_placements(placements) {
+        return Err(ScoringError::InvalidPlacements(InvalidPlacements {}));
+    }
+    let total_bps = payout_curve_bps
+        .iter()
+        .try_fold(U256::ZERO, |sum, &bps| sum.checked_add(bps))
+        .unwrap_or(U256::MAX);
+    if payout_curve_bps.is_empty()
+        || payout_curve_bps.len() > placements.len()
+        || total_bps != U256::from(MAX_BPS)
+    {
+        return Err(ScoringError::InvalidPayoutCurve(InvalidPayoutCurve {
+            length: U256::from(payout_curve_bps.len()),
+            totalBps: total_bps,
+        }));
+    }
+
+    let max_bps = U256::from(MAX_BPS);
+    let mut amounts = Vec::with_capacity(placements.len());
+    for &place in placements {
+        let group_size = placements.iter().filter(|&&other| other == place).count();
+        let first = place as usize - 1;
+        let group_bps = payout_curve_bps
+            .iter()
+            .skip(first)
+            .take(group_size)
+            .fold(U256::ZERO, |sum, &bps| sum + bps);
+        let group_amount = mul_div_down(total_fees, group_bps, max_bps)?;
+        amounts.push(group_amount / U256::from(group_size));
+    }
+
+    let paid = amounts.iter().fold(U256::ZERO, |sum, &amount| sum + amount);
+    // Valid placements always contain a 1st place
+    if let Some(first) = placements.iter().position(|&place| place == 1) {
+        amounts[first] += total_fees - paid;
+    }
+    Ok(amounts)
+}
+
+/// One player's raw inputs to `score_battle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerInput {
+    /// Seconds in range (range battles) or fees earned in USD (fee battles)
+    pub metric: U256,
+    /// LP position value in USD; only used by fee battles
+    pub lp_value_usd: U256,
+    /// Tick distance for the tightness bonus; only used by range battles
+    pub tick_distance: U256,
+    /// Weight of the player's DEX in bps, as resolved from the registry
+    pub dex_weight_bps: U256,
+}
+
+/// Everything needed to resolve a two-player battle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BattleInput {
+    /// BATTLE_TYPE_RANGE or BATTLE_TYPE_FEE
+    pub battle_type: u8,
+    /// Battle duration in seconds
+    pub duration: U256,
+    pub player_a: PlayerInput,
+    pub player_b: PlayerInput,
+    /// Fees to split between the players and the resolver
+    pub total_fees: U256,
+    pub resolver_bps: U256,
+}
+
+/// Outcome of `score_battle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BattleResult {
+    /// DEX-normalized scores
+    pub score_a: U256,
+    pub score_b: U256,
+    /// OUTCOME_PLAYER_A, OUTCOME_PLAYER_B or OUTCOME_DRAW
+    pub winner: u8,
+    /// Paid to the winner, or to each player on a draw
+    pub winner_amount: U256,
+    pub resolver_amount: U256,
+}
+
+/// Raw (un-normalized) score of one player for the given battle type.
+pub fn player_score(
+    battle_type: u8,
+    duration: U256,
+    player: &PlayerInput,
+    threshold: U256,
+    max_bonus: U256,
+) -> Result<U256, ScoringError> {
+    match battle_type {
+        BATTLE_TYPE_RANGE => {
+            range_score_with_params(player.metric, duration, player.tick_distance, threshold, max_bonus)
+        }
+        BATTLE_TYPE_FEE => fee_score(player.metric, player.lp_value_usd, duration),
+        _ => Err(ScoringError::UnknownBattleType(UnknownBattleType { battleType: battle_type })),
+    }
+}
+
+/// Resolve a two-player battle: score both players, normalize by DEX weight,
+/// compare with the draw tolerance and split the fees. A decided battle pays
+/// like `rewards`; a draw pays each player half like `draw_rewards`, so neither
+/// side takes the whole pot on a tie.
+pub fn score_battle(
+    input: &BattleInput,
+    threshold: U256,
+    max_bonus: U256,
+    tolerance_bps: U256,
+) -> Result<BattleResult, ScoringError> {
+    let normalized = |player: &PlayerInput| -> Result<U256, ScoringError> {
+        let raw = player_score(input.battle_type, input.duration, player, threshold, max_bonus)?;
+        normalize_cross_dex(raw, player.dex_weight_bps)
+    };
+    let score_a = normalized(&input.player_a)?;
+    let score_b = normalized(&input.player_b)?;
+    let winner = outcome(score_a, score_b, tolerance_bps);
+    let (winner_amount, resolver_amount) = if winner == OUTCOME_DRAW {
+        let (half, _, resolver_amount) = draw_rewards(input.total_fees, input.resolver_bps)?;
+        (half, resolver_amount)
+    } else {
+        rewards(input.total_fees, input.resolver_bps)?
+    };
+    Ok(BattleResult {
+        score_a,
+        score_b,
+        winner,
+        winner_amount,
+        resolver_amount,
+    })
+}
+
+/// Normalize a score by applying a DEX-specific weight factor.
+/// Weight is in basis points: 10000 = 1.0x, 11000 = 1.1x, 9000 = 0.9x.
+pub fn normalize_cross_dex(raw_score: U256, weight_bps: U256) -> Result<U256, ScoringError> {
+    Ok(mul_div_down(raw_score, weight_bps, U256::from(MAX_BPS))?)
+}
+
+/// Resolve the weight of a DEX registry entry.
+/// Unregistered and disabled DEXes are rejected instead of defaulting to 1.0x.
+pub fn active_dex_weight(
+    dex_type: u8,
+    registered: bool,
+    enabled: bool,
+    weight_bps: U256,
+) -> Result<U256, ScoringError> {
+    if !registered {
+        return Err(unknown_dex(dex_type));
+    }
+    if !enabled {
+        return Err(ScoringError::DexDisabled(DexDisabled { dexType: dex_type }));
+    }
+    Ok(weight_bps)
+}
+
+/// Check that a DEX weight lies within [MIN_DEX_WEIGHT_BPS, MAX_DEX_WEIGHT_BPS].
+pub fn is_valid_dex_weight(weight_bps: U256) -> bool {
+    weight_bps >= U256::from(MIN_DEX_WEIGHT_BPS) && weight_bps <= U256::from(MAX_DEX_WEIGHT_BPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E18: u64 = 1_000_000_000_000_000_000;
+
+    // ============ Range Score Tests ============
+
+    #[test]
+    fn test_range_score_full_time_no_bonus() {
+        let score = range_score(
+            U256::from(3600u64),
+            U256::from(3600u64),
+            U256::from(200u64), // beyond threshold
+        )
+        .unwrap();
+        assert_eq!(score, U256::from(E18));
+    }
+
+    #[test]
+    fn test_range_score_half_time() {
+        let score = range_score(
+            U256::from(1800u64),
+            U256::from(3600u64),
+            U256::from(200u64),
+        )
+        .unwrap();
+        assert_eq!(score, U256::from(E18 / 2));
+    }
+
+    #[test]
+    fn test_range_score_zero_total_time() {
+        let result = range_score(U256::from(1000u64), U256::ZERO, U256::ZERO);
+        assert_eq!(result, Err(ScoringError::ZeroDuration(ZeroDuration {})));
+    }
+
+    #[test]
+    fn test_range_score_in_range_exceeds_total() {
+        let result = range_score(U256::from(3601u64), U256::from(3600u64), U256::from(200u64));
+        assert_eq!(
+            result,
+            Err(ScoringError::InRangeExceedsTotal(InRangeExceedsTotal {
+                inRangeTime: U256::from(3601u64),
+                totalTime: U256::from(3600u64),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_range_score_large_times() {
+        // in_range_time * 1e18 exceeds 256 bits, but the 512-bit intermediate keeps it exact
+        let score = range_score(U256::MAX, U256::MAX, U256::from(200u64)).unwrap();
+        assert_eq!(score, U256::from(E18));
+        let score = range_score(U256::MAX / U256::from(2u64), U256::MAX, U256::from(200u64)).unwrap();
+        assert_eq!(score, U256::from(E18 / 2 - 1));
+    }
+
+    #[test]
+    fn test_range_score_zero_in_range() {
+        let score = range_score(U256::ZERO, U256::from(3600u64), U256::from(50u64)).unwrap();
+        assert_eq!(score, U256::ZERO);
+    }
+
+    #[test]
+    fn test_range_score_tight_range_max_bonus() {
+        // tick_distance = 0 → max bonus (20%)
+        let score = range_score(
+            U256::from(3600u64),
+            U256::from(3600u64),
+            U256::ZERO,
+        )
+        .unwrap();
+        let expected = U256::from(E18) + U256::from(TIGHT_RANGE_BONUS);
+        assert_eq!(score, expected);
+    }
+
+    #[test]
+    fn test_range_score_tight_range_half_bonus() {
+        // tick_distance = 50 → half of max bonus
+        let score = range_score(
+            U256::from(3600u64),
+            U256::from(3600u64),
+            U256::from(50u64),
+        )
+        .unwrap();
+        let half_bonus = TIGHT_RANGE_BONUS / 2;
+        let expected = U256::from(E18) + U256::from(half_bonus);
+        assert_eq!(score, expected);
+    }
+
+    #[test]
+    fn test_range_score_at_threshold_no_bonus() {
+        let score = range_score(
+            U256::from(3600u64),
+            U256::from(3600u64),
+            U256::from(TIGHT_RANGE_THRESHOLD),
+        )
+        .unwrap();
+        assert_eq!(score, U256::from(E18));
+    }
+
+    #[test]
+    fn test_range_score_partial_time_with_bonus() {
+        // 50% in range, max bonus
+        let score = range_score(
+            U256::from(1800u64),
+            U256::from(3600u64),
+            U256::ZERO,
+        )
+        .unwrap();
+        let base = E18 / 2;
+        let bonus = TIGHT_RANGE_BONUS / 2;
+        let expected = U256::from(base) + U256::from(bonus);
+        assert_eq!(score, expected);
+    }
+
+    #[test]
+    fn test_range_score_with_params_defaults_match() {
+        for tick_distance in [0u64, 50, 99, 100, 200] {
+            assert_eq!(
+                range_score_with_params(
+                    U256::from(2700u64),
+                    U256::from(3600u64),
+                    U256::from(tick_distance),
+                    U256::from(TIGHT_RANGE_THRESHOLD),
+                    U256::from(TIGHT_RANGE_BONUS),
+                )
+                .unwrap(),
+                range_score(U256::from(2700u64), U256::from(3600u64), U256::from(tick_distance)).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_range_score_with_params_wide_threshold() {
+        // 60-tick-spacing pool, 600-tick range, threshold 1200 → half of a 10% bonus
+        let score = range_score_with_params(
+            U256::from(3600u64),
+            U256::from(3600u64),
+            U256::from(600u64),
+            U256::from(1200u64),
+            U256::from(E18 / 10),
+        )
+        .unwrap();
+        assert_eq!(score, U256::from(E18 + E18 / 20));
+    }
+
+    #[test]
+    fn test_range_score_with_params_zero_threshold_no_bonus() {
+        let score = range_score_with_params(
+            U256::from(3600u64),
+            U256::from(3600u64),
+            U256::ZERO,
+            U256::ZERO,
+            U256::from(TIGHT_RANGE_BONUS),
+        )
+        .unwrap();
+        assert_eq!(score, U256::from(E18));
+    }
+
+    #[test]
+    fn test_range_params_bounds() {
+        assert!(is_valid_range_params(U256::ZERO, U256::ZERO));
+        assert!(is_valid_range_params(
+            U256::from(MAX_RANGE_THRESHOLD),
+            U256::from(MAX_RANGE_BONUS)
+        ));
+        assert!(!is_valid_range_params(
+            U256::from(MAX_RANGE_THRESHOLD + 1),
+            U256::from(TIGHT_RANGE_BONUS)
+        ));
+        assert!(!is_valid_range_params(
+            U256::from(TIGHT_RANGE_THRESHOLD),
+            U256::from(MAX_RANGE_BONUS) + U256::from(1u64)
+        ));
+    }
+
+    #[test]
+    fn test_scoring_error_abi_encoding() {
+        use alloy_sol_types::SolError;
+
+        let encoded: Vec<u8> = ScoringError::InRangeExceedsTotal(InRangeExceedsTotal {
+            inRangeTime: U256::from(2u64),
+            totalTime: U256::from(1u64),
+        })
+        .into();
+        assert_eq!(&encoded[..4], InRangeExceedsTotal::SELECTOR.as_slice());
+        assert_eq!(encoded.len(), 4 + 64);
+    }
+
+    #[test]
+    fn test_math_error_conversion() {
+        assert_eq!(ScoringError::from(MathError::Overflow), ScoringError::Overflow(Overflow {}));
+        assert_eq!(
+            ScoringError::from(MathError::DivisionByZero),
+            ScoringError::DivisionByZero(DivisionByZero {})
+        );
+        assert_eq!(ScoringError::from(MathError::Undefined), ScoringError::MathUndefined(MathUndefined {}));
+    }
+
+    // ============ Score Breakdown Tests ============
+
+    #[test]
+    fn test_explain_range_score_steps() {
+        let breakdown = explain_range_score(
+            U256::from(1800u64),
+            U256::from(3600u64),
+            U256::from(50u64),
+            U256::from(TIGHT_RANGE_THRESHOLD),
+            U256::from(TIGHT_RANGE_BONUS),
+            U256::from(11_000u64),
+        )
+        .unwrap();
+        assert_eq!(
+            breakdown,
+            ScoreBreakdown {
+                base_score: U256::from(E18 / 2),
+                bonus: U256::from(TIGHT_RANGE_BONUS / 2),
+                raw_score: U256::from(E18 / 2 + E18 / 20),
+                dex_weight_bps: U256::from(11_000u64),
+                final_score: U256::from(605_000_000_000_000_000u64),
+            }
+        );
+    }
+
+    #[test]
+    fn test_explain_range_score_matches_range_score() {
+        for (in_range, tick) in [(3600u64, 0u64), (1234, 17), (0, 5), (3599, 99), (3600, 100), (42, 10_000)] {
+            let (in_range, total, tick) = (U256::from(in_range), U256::from(3600u64), U256::from(tick));
+            let (threshold, bonus) = (U256::from(TIGHT_RANGE_THRESHOLD), U256::from(TIGHT_RANGE_BONUS));
+            let breakdown =
+                explain_range_score(in_range, total, tick, threshold, bonus, U256::from(9_000u64)).unwrap();
+            let raw = range_score(in_range, total, tick).unwrap();
+            assert_eq!(breakdown.raw_score, raw);
+            assert_eq!(breakdown.final_score, normalize_cross_dex(raw, U256::from(9_000u64)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_explain_fee_score() {
+        let (fees, lp, duration) = (U256::from(1_000u64), U256::from(100_000u64), U256::from(3600u64));
+        let breakdown = explain_fee_score(fees, lp, duration, U256::from(20_000u64)).unwrap();
+        let raw = fee_score(fees, lp, duration).unwrap();
+        assert_eq!(breakdown.base_score, raw);
+        assert_eq!(breakdown.bonus, U256::ZERO);
+        assert_eq!(breakdown.raw_score, raw);
+        assert_eq!(breakdown.final_score, raw * U256::from(2u64));
+    }
+
+    #[test]
+    fn test_explain_propagates_errors() {
+        assert_eq!(
+            explain_fee_score(U256::from(1u64), U256::ZERO, U256::from(1u64), U256::from(MAX_BPS)),
+            Err(ScoringError::ZeroLpValue(ZeroLpValue {}))
+        );
+        assert_eq!(
+            explain_range_score(U256::ZERO, U256::ZERO, U256::ZERO, U256::ZERO, U256::ZERO, U256::ONE),
+            Err(ScoringError::ZeroDuration(ZeroDuration {}))
+        );
+    }
+
+    // ============ Fee Score Tests ============
+
+    #[test]
+    fn test_fee_score_basic() {
+        let score = fee_score(
+            U256::from(10u64) * U256::from(100_000_000u64), // $10
+            U256::from(1000u64) * U256::from(100_000_000u64), // $1000
+            U256::from(3600u64),
+        )
+        .unwrap();
+        let expected = U256::from(E18) / U256::from(360_000u64);
+        assert_eq!(score, expected);
+    }
+
+    #[test]
+    fn test_fee_score_zero_fees() {
+        assert_eq!(fee_score(U256::ZERO, U256::from(1000u64), U256::from(3600u64)).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_fee_score_zero_lp_value() {
+        assert_eq!(
+            fee_score(U256::from(100u64), U256::ZERO, U256::from(3600u64)),
+            Err(ScoringError::ZeroLpValue(ZeroLpValue {}))
+        );
+    }
+
+    #[test]
+    fn test_fee_score_zero_duration() {
+        assert_eq!(
+            fee_score(U256::from(100u64), U256::from(1000u64), U256::ZERO),
+            Err(ScoringError::ZeroDuration(ZeroDuration {}))
+        );
+    }
+
+    #[test]
+    fn test_fee_score_overflow() {
+        assert_eq!(
+            fee_score(U256::MAX, U256::from(1000u64), U256::from(3600u64)),
+            Err(ScoringError::Overflow(Overflow {}))
+        );
+    }
+
+    #[test]
+    fn test_fee_score_large_lp_value() {
+        // lp_value_usd * duration no longer needs to fit in 256 bits
+        assert_eq!(
+            fee_score(U256::from(100u64), U256::MAX, U256::from(3600u64)).unwrap(),
+            U256::ZERO
+        );
+        let lp_value = U256::MAX / U256::from(E18);
+        assert_eq!(fee_score(lp_value, lp_value, U256::from(3600u64)).unwrap(), U256::from(E18 / 3600));
+    }
+
+    #[test]
+    fn test_fee_score_higher_fees_higher_score() {
+        let low = fee_score(U256::from(10u64), U256::from(1000u64), U256::from(3600u64)).unwrap();
+        let high = fee_score(U256::from(100u64), U256::from(1000u64), U256::from(3600u64)).unwrap();
+        assert!(high > low);
+    }
+
+    #[test]
+    fn test_fee_score_larger_position_lower_rate() {
+        let small = fee_score(U256::from(100u64), U256::from(1000u64), U256::from(3600u64)).unwrap();
+        let large = fee_score(U256::from(100u64), U256::from(2000u64), U256::from(3600u64)).unwrap();
+        assert!(small > large);
+    }
+
+    // ============ Fee Score From Tokens Tests ============
+
+    fn token(amount: U256, decimals: u8, price: U256, price_decimals: u8) -> TokenFees {
+        TokenFees {
+            amount,
+            decimals,
+            price,
+            price_decimals,
+        }
+    }
+
+    #[test]
+    fn test_token_usd_value_weth_and_usdc() {
+        // 1.5 WETH at $3000.12345678 (Chainlink 8 decimals)
+        let weth = token(U256::from(1_500_000_000_000_000_000u64), 18, U256::from(300_012_345_678u64), 8);
+        assert_eq!(token_usd_value(&weth).unwrap(), U256::from(450_018_518_517u64));
+        // 250 USDC at $0.9998 (4 price decimals)
+        let usdc = token(U256::from(250_000_000u64), 6, U256::from(9_998u64), 4);
+        assert_eq!(token_usd_value(&usdc).unwrap(), U256::from(24_995_000_000u64));
+    }
+
+    #[test]
+    fn test_token_usd_value_rounds_down() {
+        // 1 wei of an 18-decimal token at $1 is far below one USD unit
+        let dust = token(U256::from(1u64), 18, U256::from(100_000_000u64), 8);
+        assert_eq!(token_usd_value(&dust).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_token_usd_value_few_decimals_scales_up() {
+        // 3 whole units of a 0-decimal token at $2 (0 price decimals) = $6.00000000
+        let whole = token(U256::from(3u64), 0, U256::from(2u64), 0);
+        assert_eq!(token_usd_value(&whole).unwrap(), U256::from(600_000_000u64));
+    }
+
+    #[test]
+    fn test_token_usd_value_errors() {
+        let bad = token(U256::from(1u64), MAX_TOKEN_DECIMALS + 1, U256::from(1u64), 8);
+        assert_eq!(
+            token_usd_value(&bad),
+            Err(ScoringError::InvalidDecimals(InvalidDecimals { decimals: MAX_TOKEN_DECIMALS + 1 }))
+        );
+        let bad = token(U256::from(1u64), 18, U256::from(1u64), 255);
+        assert!(matches!(token_usd_value(&bad), Err(ScoringError::InvalidDecimals(_))));
+        let huge = token(U256::MAX, 0, U256::from(2u64), 8);
+        assert_eq!(token_usd_value(&huge), Err(ScoringError::Overflow(Overflow {})));
+        // Max decimals on both sides still works
+        let max = token(U256::MAX, MAX_TOKEN_DECIMALS, U256::from(1u64), MAX_TOKEN_DECIMALS);
+        assert!(token_usd_value(&max).is_ok());
+    }
+
+    #[test]
+    fn test_fee_score_from_tokens_matches_usd_path() {
+        let weth = token(U256::from(10_000_000_000_000_000u64), 18, U256::from(300_000_000_000u64), 8); // $30
+        let usdc = token(U256::from(20_000_000u64), 6, U256::from(100_000_000u64), 8); // $20
+        let lp_value = U256::from(1_000u64) * U256::from(100_000_000u64);
+        let duration = U256::from(3600u64);
+        let score = fee_score_from_tokens(&weth, &usdc, lp_value, duration).unwrap();
+        assert_eq!(score, fee_score(U256::from(5_000_000_000u64), lp_value, duration).unwrap());
+        assert_eq!(
+            fee_score_from_tokens(&weth, &usdc, U256::ZERO, duration),
+            Err(ScoringError::ZeroLpValue(ZeroLpValue {}))
+        );
+    }
+
+    // ============ Donation-Adjusted Fee Tests ============
+
+    /// $1000 position in 8-decimal USD
+    const LP_VALUE: u64 = 100_000_000_000;
+
+    /// Fee growth per unit of liquidity from donating `amount` to `pool_liquidity`, as V4 records it
+    fn donation_growth(amount: u64, pool_liquidity: u128) -> U256 {
+        (U256::from(amount) << 128) / U256::from(pool_liquidity)
+    }
+
+    #[test]
+    fn test_self_donation_attack_gains_nothing() {
+        let lp = U256::from(LP_VALUE);
+        let duration = U256::from(86_400u64);
+        let usdc = |amount| token(amount, 6, U256::from(100_000_000u64), 8);
+        let organic = U256::from(2_000_000u64); // 2 USDC of swap fees
+        // Player holds 40% of in-range liquidity and donates 100 USDC to their own pool
+        let (position, pool) = (400u128, 1_000u128);
+        let donated = 100_000_000u64;
+        let collected = organic + U256::from(donated * 2 / 5);
+
+        let value = |fees| fee_score_from_tokens(&usdc(fees), &usdc(U256::ZERO), lp, duration).unwrap();
+        assert!(value(collected) > value(organic));
+
+        let adjusted = donation_adjusted_fees(collected, donation_growth(donated, pool), position).unwrap();
+        assert_eq!(adjusted, organic);
+        assert_eq!(value(adjusted), value(organic));
+    }
+
+    #[test]
+    fn test_donation_share_survives_pool_liquidity_change() {
+        let organic = U256::from(1_234u64);
+        let position = 250u128;
+        // Donations land while the player holds 25%, then 12.5%, of in-range liquidity
+        let first = donation_growth(10_000, 1_000);
+        let second = donation_growth(6_000, 2_000);
+        let collected = organic + U256::from(2_500u64 + 750);
+
+        // The pool then grows to 100_000 before resolution; a share taken from that
+        // liquidity would only remove 0.25% of the donations and keep the rest as score
+        let resolution_share = U256::from(16_000u64 * 250 / 100_000);
+        assert!(collected - resolution_share > organic);
+
+        let adjusted = donation_adjusted_fees(collected, first + second, position).unwrap();
+        assert_eq!(adjusted, organic);
+    }
+
+    #[test]
+    fn test_donation_to_sole_lp_is_fully_removed() {
+        let fees = U256::from(1_234u64 + 5_000);
+        let growth = donation_growth(5_000, 7);
+        assert_eq!(donation_adjusted_fees(fees, growth, 7).unwrap(), U256::from(1_234u64));
+        // No donations leaves fees untouched
+        assert_eq!(donation_adjusted_fees(fees, U256::ZERO, 7).unwrap(), fees);
+    }
+
+    #[test]
+    fn test_donation_adjustment_rounds_against_player() {
+        // 10 units donated to 3 liquidity: the 1-liquidity position's share rounds up to 4
+        let growth = donation_growth(10, 3);
+        assert_eq!(donation_adjusted_fees(U256::from(10u64), growth, 1).unwrap(), U256::from(6u64));
+        // Fees never go negative
+        assert_eq!(donation_adjusted_fees(U256::from(3u64), growth, 1).unwrap(), U256::ZERO);
+        // Liquidity is below 2^128, so even the largest growth cannot overflow
+        assert!(donation_adjusted_fees(U256::MAX, U256::MAX, u128::MAX).is_ok());
+    }
+
+    // ============ Pool-Relative Fee Score Tests ============
+
+    #[test]
+    fn test_fee_apr() {
+        let lp = U256::from(LP_VALUE);
+        // 1% of the position over 1/10 of a year is a 10% APR
+        let apr = fee_apr(lp / U256::from(100u64), lp, U256::from(SECONDS_PER_YEAR / 10)).unwrap();
+        assert_eq!(apr, U256::from(E18 / 10));
+        assert_eq!(fee_apr(U256::ZERO, lp, U256::from(1u64)).unwrap(), U256::ZERO);
+        assert_eq!(fee_apr(lp, U256::ZERO, U256::from(1u64)), Err(ScoringError::ZeroLpValue(ZeroLpValue {})));
+        assert_eq!(fee_apr(lp, lp, U256::ZERO), Err(ScoringError::ZeroDuration(ZeroDuration {})));
+    }
+
+    #[test]
+    fn test_pool_relative_fee_score_levels_fee_tiers() {
+        let lp = U256::from(LP_VALUE);
+        let duration = U256::from(SECONDS_PER_YEAR / 10);
+        // 1% pool (pool APR 40%): player earns 5% of value over the window = 50% APR
+        let high_tier = pool_relative_fee_score(
+            lp / U256::from(20u64),
+            lp,
+            duration,
+            U256::from(E18 / 10 * 4),
+        )
+        .unwrap();
+        // 0.05% pool (pool APR 4%): player earns 1% over the window = 10% APR
+        let low_tier =
+            pool_relative_fee_score(lp / U256::from(100u64), lp, duration, U256::from(E18 / 100 * 4))
+                .unwrap();
+        // Raw yield favours the 1% pool, but the 0.05% player beat their pool by more
+        assert_eq!(high_tier, U256::from(E18 / 4 * 5));
+        assert_eq!(low_tier, U256::from(E18 / 2 * 5));
+        assert!(low_tier > high_tier);
+        // Matching the pool scores exactly 1e18
+        let pool_apr = U256::from(E18 / 10);
+        let matched = pool_relative_fee_score(lp / U256::from(100u64), lp, duration, pool_apr);
+        assert_eq!(matched.unwrap(), U256::from(E18));
+    }
+
+    #[test]
+    fn test_pool_relative_fee_score_validation() {
+        let lp = U256::from(LP_VALUE);
+        let year = U256::from(SECONDS_PER_YEAR);
+        // No pool data: there is no made-up baseline to fall back on
+        assert_eq!(
+            pool_relative_fee_score(lp, lp, year, U256::ZERO),
+            Err(ScoringError::ZeroPoolFeeApr(ZeroPoolFeeApr {}))
+        );
+        // Position-side errors still surface once a pool APR is given
+        assert_eq!(
+            pool_relative_fee_score(lp, U256::ZERO, year, U256::from(E18)),
+            Err(ScoringError::ZeroLpValue(ZeroLpValue {}))
+        );
+        assert_eq!(
+            pool_relative_fee_score(lp, lp, U256::ZERO, U256::from(E18)),
+            Err(ScoringError::ZeroDuration(ZeroDuration {}))
+        );
+    }
+
+    // ============ Fees Owed Tests ============
+
+    #[test]
+    fn test_position_fees_owed() {
+        let q128 = U256::from(1u64) << 128;
+        let (end0, end1) = (q128 * U256::from(3u64), q128 / U256::from(2u64));
+        let (fees0, fees1) = position_fees_owed(q128, U256::ZERO, end0, end1, 1_000).unwrap();
+        assert_eq!((fees0, fees1), (U256::from(2_000u64), U256::from(500u64)));
+        // No growth, no fees
+        assert_eq!(position_fees_owed(q128, q128, q128, q128, 1_000).unwrap(), (U256::ZERO, U256::ZERO));
+    }
+
+    #[test]
+    fn test_position_fees_owed_wraparound() {
+        let q128 = U256::from(1u64) << 128;
+        // Token1 growth wrapped past 2^256 during the battle: start near the top, end near zero
+        let start1 = U256::ZERO.wrapping_sub(q128 * U256::from(2u64));
+        let end1 = q128;
+        let (fees0, fees1) = position_fees_owed(U256::ZERO, start1, U256::ZERO, end1, 10).unwrap();
+        assert_eq!(fees0, U256::ZERO);
+        assert_eq!(fees1, U256::from(30u64));
+    }
+
+    // ============ Net PnL Tests ============
+
+    const Q96: u128 = 1 << 96;
+
+    fn pnl_input(entry: U256, exit: U256, fees0: u64, fees1: u64) -> NetPnlInput {
+        NetPnlInput {
+            entry_sqrt_price_x96: entry,
+            exit_sqrt_price_x96: exit,
+            tick_lower: -6_000,
+            tick_upper: 6_000,
+            liquidity: 1_000_000_000_000_000_000,
+            fees0: U256::from(fees0),
+            fees1: U256::from(fees1),
+        }
+    }
+
+    #[test]
+    fn test_net_pnl_unchanged_price_no_fees_is_break_even() {
+        let price = U256::from(Q96);
+        let score = net_pnl_score(&pnl_input(price, price, 0, 0)).unwrap();
+        // Rounding of the token amounts can cost at most a few wei of value
+        assert!(score <= U256::from(E18) && score >= U256::from(E18 - 10));
+    }
+
+    #[test]
+    fn test_net_pnl_price_move_shows_impermanent_loss() {
+        // Price rises ~30% (tick 2624): the LP sold token0 on the way up and lags HODL
+        let entry = U256::from(Q96);
+        let exit = sqrt_ratio_at_tick(2_624).unwrap();
+        let no_fees = net_pnl_score(&pnl_input(entry, exit, 0, 0)).unwrap();
+        assert!(no_fees < U256::from(E18));
+
+        // Same move in the other direction also loses to HODL
+        let down = sqrt_ratio_at_tick(-2_624).unwrap();
+        assert!(net_pnl_score(&pnl_input(entry, down, 0, 0)).unwrap() < U256::from(E18));
+    }
+
+    #[test]
+    fn test_net_pnl_fees_offset_impermanent_loss() {
+        let entry = U256::from(Q96);
+        let exit = sqrt_ratio_at_tick(2_624).unwrap();
+        let no_fees = net_pnl_score(&pnl_input(entry, exit, 0, 0)).unwrap();
+        let small_fees = net_pnl_score(&pnl_input(entry, exit, 0, 1_000_000_000_000_000)).unwrap();
+        let fee = 10_000_000_000_000_000;
+        let big_fees = net_pnl_score(&pnl_input(entry, exit, fee, fee)).unwrap();
+        assert!(small_fees > no_fees);
+        assert!(big_fees > U256::from(E18));
+        // A fee-heavy position that suffered more IL can still lose on net
+        let far_exit = sqrt_ratio_at_tick(5_000).unwrap();
+        let heavy_il = net_pnl_score(&pnl_input(entry, far_exit, 0, 1_000_000_000_000_000)).unwrap();
+        assert!(heavy_il < small_fees);
+    }
+
+    #[test]
+    fn test_net_pnl_errors() {
+        let price = U256::from(Q96);
+        let mut input = pnl_input(price, price, 0, 0);
+        input.tick_lower = 60;
+        input.tick_upper = 60;
+        assert_eq!(
+            net_pnl_score(&input),
+            Err(ScoringError::InvalidTickRange(InvalidTickRange { tickLower: 60, tickUpper: 60 }))
+        );
+        input.tick_lower = -900_000;
+        assert_eq!(net_pnl_score(&input), Err(ScoringError::TickOutOfRange(TickOutOfRange {})));
+        let mut input = pnl_input(price, price, 5, 5);
+        input.liquidity = 0;
+        assert_eq!(net_pnl_score(&input), Err(ScoringError::ZeroLpValue(ZeroLpValue {})));
+    }
+
+    // ============ Position Amounts Tests ============
+
+    #[test]
+    fn test_position_amounts() {
+        let liquidity = 1_000_000_000_000_000_000u128;
+        let price = sqrt_ratio_at_tick(0).unwrap();
+        // In range: both tokens, symmetric around tick 0
+        let (amount0, amount1) = position_amounts(liquidity, -600, 600, price).unwrap();
+        assert!(!amount0.is_zero() && !amount1.is_zero());
+        assert!(amount0.abs_diff(amount1) <= U256::from(1u64));
+        // Below the range: all token0; above: all token1
+        let (amount0, amount1) = position_amounts(liquidity, 600, 1_200, price).unwrap();
+        assert!(!amount0.is_zero() && amount1.is_zero());
+        let (amount0, amount1) = position_amounts(liquidity, -1_200, -600, price).unwrap();
+        assert!(amount0.is_zero() && !amount1.is_zero());
+        // Zero liquidity holds nothing
+        assert_eq!(position_amounts(0, -600, 600, price).unwrap(), (U256::ZERO, U256::ZERO));
+    }
+
+    #[test]
+    fn test_position_amounts_errors() {
+        assert_eq!(
+            position_amounts(1, 600, 600, U256::from(1u64) << 96),
+            Err(ScoringError::InvalidTickRange(InvalidTickRange {
+                tickLower: 600,
+                tickUpper: 600,
+            }))
+        );
+        assert_eq!(
+            position_amounts(1, -900_000, 600, U256::from(1u64) << 96),
+            Err(ScoringError::TickOutOfRange(TickOutOfRange {}))
+        );
+    }
+
+    // ============ Position Valuation Tests ============
+
+    /// sqrtPriceX96 for a WETH/USDC pool (18 and 6 decimals) at $2000 per WETH:
+    /// price = 2000e6 / 1e18 = 2e-9 raw token1 per raw token0
+    fn weth_usdc_sqrt_price() -> U256 {
+        sqrt_ratio_at_tick(-200_311).unwrap()
+    }
+
+    #[test]
+    fn test_position_value_usd_in_range() {
+        let sqrt_price = weth_usdc_sqrt_price();
+        let liquidity = 1_000_000_000_000_000u128;
+        let weth = U256::from(200_000_000_000u64); // $2000.00000000
+        let usdc = U256::from(100_000_000u64); // $1.00000000
+        let value = position_value_usd(liquidity, -201_000, -199_620, sqrt_price, weth, usdc, 18, 6).unwrap();
+        assert_eq!(
+            (value.amount0, value.amount1),
+            position_amounts(liquidity, -201_000, -199_620, sqrt_price).unwrap()
+        );
+        // Value is the sum of both legs priced independently
+        let expected = value.amount0 * weth / U256::from(E18) + value.amount1 * U256::from(100u64);
+        assert_eq!(value.value_usd, expected);
+        // Both legs are worth roughly the same near the middle of the range
+        let leg0 = value.amount0 * weth / U256::from(E18);
+        let leg1 = value.amount1 * U256::from(100u64);
+        assert!(leg0.abs_diff(leg1) * U256::from(10u64) < leg0);
+    }
+
+    #[test]
+    fn test_position_value_usd_single_sided() {
+        let sqrt_price = weth_usdc_sqrt_price();
+        let weth = U256::from(200_000_000_000u64);
+        let usdc = U256::from(100_000_000u64);
+        // Range above the price holds only WETH; the USDC price is irrelevant
+        let above = position_value_usd(1_000_000, -199_000, -198_000, sqrt_price, weth, usdc, 18, 6).unwrap();
+        assert!(above.amount1.is_zero());
+        let repriced = position_value_usd(1_000_000, -199_000, -198_000, sqrt_price, weth, U256::ZERO, 18, 6);
+        assert_eq!(repriced.unwrap().value_usd, above.value_usd);
+        // Invalid decimals are rejected
+        assert_eq!(
+            position_value_usd(1_000_000, -199_000, -198_000, sqrt_price, weth, usdc, 39, 6),
+            Err(ScoringError::InvalidDecimals(InvalidDecimals { decimals: 39 }))
+        );
+    }
+
+    #[test]
+    fn test_value_within_tolerance() {
+        let bps = U256::from(500u64); // 5%, the BattleArena default
+        let value = |v: u64| U256::from(v);
+        assert!(value_within_tolerance(value(10_000), value(9_500), bps).unwrap());
+        assert!(value_within_tolerance(value(9_500), value(10_000), bps).unwrap());
+        assert!(!value_within_tolerance(value(10_000), value(9_499), bps).unwrap());
+        assert!(value_within_tolerance(U256::ZERO, U256::ZERO, bps).unwrap());
+        assert!(!value_within_tolerance(value(1), U256::ZERO, bps).unwrap());
+        // Large values do not overflow the tolerance math
+        assert!(!value_within_tolerance(U256::MAX, U256::MAX - U256::from(1u64), U256::ZERO).unwrap());
+        assert!(value_within_tolerance(U256::MAX, U256::MAX - U256::from(1u64), U256::from(1u64)).unwrap());
+    }
+
+    // ============ Hybrid Score Tests ============
+
+    /// Round fee ceiling so rescaled test values are exact
+    const FEE_CEILING: u64 = 40_000_000_000;
+
+    fn hybrid(range: U256, fee: U256, weight_bps: u64) -> U256 {
+        hybrid_score(
+            range,
+            fee,
+            U256::from(TIGHT_RANGE_BONUS),
+            U256::from(FEE_CEILING),
+            U256::from(weight_bps),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rescale_range_score() {
+        let bonus = U256::from(TIGHT_RANGE_BONUS);
+        // Full time at the tightest range is the best possible score
+        let best = range_score(U256::from(3600u64), U256::from(3600u64), U256::ZERO).unwrap();
+        assert_eq!(rescale_range_score(best, bonus).unwrap(), U256::from(E18));
+        assert_eq!(rescale_range_score(U256::ZERO, bonus).unwrap(), U256::ZERO);
+        // 1.2e18 max: 0.6e18 rescales to 0.5e18
+        assert_eq!(rescale_range_score(U256::from(E18 * 6 / 10), bonus).unwrap(), U256::from(E18 / 2));
+        // Without a bonus, range scores are already 0..=1e18
+        assert_eq!(rescale_range_score(U256::from(E18 / 3), U256::ZERO).unwrap(), U256::from(E18 / 3));
+    }
+
+    #[test]
+    fn test_rescale_fee_score() {
+        let ceiling = U256::from(FEE_CEILING);
+        assert_eq!(rescale_fee_score(U256::ZERO, ceiling).unwrap(), U256::ZERO);
+        assert_eq!(rescale_fee_score(ceiling / U256::from(4u64), ceiling).unwrap(), U256::from(E18 / 4));
+        assert_eq!(rescale_fee_score(ceiling, ceiling).unwrap(), U256::from(E18));
+        // Above the ceiling is capped
+        assert_eq!(rescale_fee_score(U256::MAX, ceiling).unwrap(), U256::from(E18));
+    }
+
+    #[test]
+    fn test_hybrid_default_fee_ceiling_is_full_yearly_yield() {
+        // Earning the position's full value in fees over a year hits the ceiling
+        let value = U256::from(1_000u64) * U256::from(100_000_000u64);
+        let yearly = fee_score(value, value, U256::from(SECONDS_PER_YEAR)).unwrap();
+        assert_eq!(yearly, U256::from(DEFAULT_HYBRID_FEE_CEILING));
+        assert_eq!(rescale_fee_score(yearly, yearly).unwrap(), U256::from(E18));
+    }
+
+    #[test]
+    fn test_hybrid_score_weight_extremes() {
+        let ceiling = U256::from(FEE_CEILING);
+        let range = U256::from(E18 * 6 / 10); // rescales to 0.5e18
+        let fee = ceiling / U256::from(5u64); // rescales to 0.2e18
+        // Pure range and pure fee weights return the rescaled score of one side
+        assert_eq!(hybrid(range, fee, MAX_BPS), U256::from(E18 / 2));
+        assert_eq!(hybrid(range, fee, 0), U256::from(E18 / 5));
+        // 50/50 averages them
+        assert_eq!(hybrid(range, fee, 5_000), U256::from(E18 / 100 * 35));
+        // Best on both sides is exactly 1e18 at any weight; worst is zero
+        let best_range = U256::from(E18 + TIGHT_RANGE_BONUS);
+        for weight in [0, 2_500, 5_000, 10_000] {
+            assert_eq!(hybrid(best_range, U256::MAX, weight), U256::from(E18));
+            assert_eq!(hybrid(U256::ZERO, U256::ZERO, weight), U256::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_hybrid_score_monotonic_in_weight() {
+        // When range beats fees, shifting weight toward range only raises the score
+        let (range, fee) = (U256::from(E18), U256::from(FEE_CEILING / 10));
+        let mut previous = U256::ZERO;
+        for weight in (0..=MAX_BPS).step_by(1_000) {
+            let score = hybrid(range, fee, weight);
+            assert!(score >= previous);
+            assert!(score <= U256::from(E18));
+            previous = score;
+        }
+    }
+
+    #[test]
+    fn test_hybrid_score_invalid_params() {
+        let (range, fee) = (U256::from(E18), U256::from(1u64));
+        assert_eq!(
+            hybrid_score(range, fee, U256::ZERO, U256::from(1u64), U256::from(MAX_BPS + 1)),
+            Err(ScoringError::InvalidHybridParams(InvalidHybridParams {
+                rangeWeightBps: U256::from(MAX_BPS + 1),
+                feeCeiling: U256::from(1u64),
+            }))
+        );
+        assert!(matches!(
+            hybrid_score(range, fee, U256::ZERO, U256::ZERO, U256::from(5_000u64)),
+            Err(ScoringError::InvalidHybridParams(_))
+        ));
+    }
+
+    // ============ Checkpoint In-Range Time Tests ============
+
+    fn checkpoints(points: &[(u64, i32)]) -> Vec<TickCheckpoint> {
+        points.iter().map(|&(timestamp, tick)| TickCheckpoint { timestamp, tick }).collect()
+    }
+
+    const WINDOW: BattleWindow = BattleWindow { start: 1_000, end: 4_600 };
+
+    #[test]
+    fn test_checkpoints_exact_in_range_time() {
+        // In range [-100, 100) for 1000..1600 and 2800..4600, out for 1600..2800
+        let points = checkpoints(&[(1_000, 0), (1_600, 150), (2_200, -101), (2_800, -100), (4_000, 99)]);
+        let in_range = in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 3_600).unwrap();
+        assert_eq!(in_range, 600 + 1_800);
+    }
+
+    #[test]
+    fn test_checkpoints_upper_tick_is_out_of_range() {
+        let points = checkpoints(&[(1_000, 100)]);
+        assert_eq!(in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 3_600).unwrap(), 0);
+        let points = checkpoints(&[(1_000, -100)]);
+        assert_eq!(in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 3_600).unwrap(), 3_600);
+    }
+
+    #[test]
+    fn test_checkpoints_clamped_to_window() {
+        // Before the start only the latest checkpoint matters; after the end is ignored
+        let points = checkpoints(&[(0, 500), (900, 0), (4_000, 500), (5_000, 0)]);
+        let in_range = in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 3_600).unwrap();
+        assert_eq!(in_range, 3_000);
+    }
+
+    #[test]
+    fn test_checkpoints_score_matches_range_score() {
+        let points = checkpoints(&[(1_000, 0), (2_800, 500)]);
+        let in_range = in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 3_600).unwrap();
+        let score = range_score(U256::from(in_range), U256::from(3_600u64), U256::from(200u64)).unwrap();
+        assert_eq!(score, U256::from(E18 / 2));
+    }
+
+    #[test]
+    fn test_checkpoints_validation() {
+        let ok = checkpoints(&[(1_000, 0)]);
+        assert_eq!(
+            in_range_time_from_checkpoints(&ok, -100, 100, BattleWindow { start: 5, end: 5 }, 3_600),
+            Err(ScoringError::InvalidBattleWindow(InvalidBattleWindow {
+                start: U256::from(5u64),
+                end: U256::from(5u64),
+            }))
+        );
+        assert!(matches!(
+            in_range_time_from_checkpoints(&ok, 100, 100, WINDOW, 3_600),
+            Err(ScoringError::InvalidTickRange(_))
+        ));
+        assert_eq!(
+            in_range_time_from_checkpoints(&checkpoints(&[(1_001, 0)]), -100, 100, WINDOW, 3_600),
+            Err(ScoringError::CheckpointsDoNotCover(CheckpointsDoNotCover {
+                start: U256::from(1_000u64),
+                firstTimestamp: U256::from(1_001u64),
+            }))
+        );
+        assert!(matches!(
+            in_range_time_from_checkpoints(&[], -100, 100, WINDOW, 3_600),
+            Err(ScoringError::CheckpointsDoNotCover(_))
+        ));
+        let unordered = checkpoints(&[(1_000, 0), (2_000, 0), (2_000, 5)]);
+        assert_eq!(
+            in_range_time_from_checkpoints(&unordered, -100, 100, WINDOW, 3_600),
+            Err(ScoringError::CheckpointsOutOfOrder(CheckpointsOutOfOrder { index: U256::from(2u64) }))
+        );
+    }
+
+    #[test]
+    fn test_checkpoints_reject_large_gaps() {
+        let points = checkpoints(&[(1_000, 0), (1_500, 0), (3_000, 0), (4_000, 0)]);
+        assert_eq!(
+            in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 1_000),
+            Err(ScoringError::CheckpointGapTooLarge(CheckpointGapTooLarge {
+                from: U256::from(1_500u64),
+                to: U256::from(3_000u64),
+            }))
+        );
+        assert_eq!(in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 1_500).unwrap(), 3_600);
+        // The stretch from the last checkpoint to the end counts too
+        let points = checkpoints(&[(1_000, 0), (2_000, 0)]);
+        assert!(matches!(
+            in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 2_000),
+            Err(ScoringError::CheckpointGapTooLarge(_))
+        ));
+        // An old checkpoint before the start only counts from the start
+        let points = checkpoints(&[(0, 0), (1_500, 0), (2_500, 0), (3_500, 0)]);
+        assert!(in_range_time_from_checkpoints(&points, -100, 100, WINDOW, 1_100).is_ok());
+    }
+
+    // ============ Density Range Score Tests ============
+
+    #[test]
+    fn test_liquidity_share() {
+        assert_eq!(liquidity_share(1, 4).unwrap(), U256::from(E18 / 4));
+        assert_eq!(liquidity_share(0, 0).unwrap(), U256::ZERO);
+        assert_eq!(liquidity_share(u128::MAX, u128::MAX).unwrap(), U256::from(E18));
+        // More than the active liquidity means the position is out of range
+        assert_eq!(liquidity_share(5, 4).unwrap(), U256::ZERO);
+        assert_eq!(liquidity_share(u128::MAX, 1).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_density_range_score_weights_by_share() {
+        let cap = U256::from(1_000u64); // 10%
+        // Half the time in range with 5% of active liquidity: 0.5 * 0.05 / 0.10
+        let score = density_range_score(U256::from(50u64), U256::from(100u64), 5, 100, cap).unwrap();
+        assert_eq!(score, U256::from(E18 / 4));
+        // Dust position scores next to nothing however long it stays in range
+        let full_time = U256::from(100u64);
+        let dust = density_range_score(full_time, full_time, 1, 1_000_000_000, cap).unwrap();
+        assert_eq!(dust, U256::from(E18 / 100_000_000));
+        // Out of range the whole time scores zero
+        assert_eq!(density_range_score(U256::ZERO, U256::from(100u64), 5, 100, cap).unwrap(), U256::ZERO);
+        // Liquidity above the active total is out of range at read time, not an error
+        assert_eq!(density_range_score(full_time, full_time, 101, 100, cap).unwrap(), U256::ZERO);
+    }
+
+    #[test]
+    fn test_density_range_score_caps_share() {
+        let cap = U256::from(1_000u64);
+        let at_cap = density_range_score(U256::from(100u64), U256::from(100u64), 10, 100, cap).unwrap();
+        let whale = density_range_score(U256::from(100u64), U256::from(100u64), 100, 100, cap).unwrap();
+        assert_eq!(at_cap, U256::from(E18));
+        assert_eq!(whale, at_cap);
+        // A whale out of range half the time loses to a capped player in range all the time
+        let whale = density_range_score(U256::from(50u64), U256::from(100u64), 100, 100, cap).unwrap();
+        assert!(whale < at_cap);
+    }
+
+    #[test]
+    fn test_density_range_score_errors() {
+        let cap = U256::from(1_000u64);
+        assert_eq!(
+            density_range_score(U256::ZERO, U256::ZERO, 1, 1, cap),
+            Err(ScoringError::ZeroDuration(ZeroDuration {}))
+        );
+        assert!(matches!(
+            density_range_score(U256::from(2u64), U256::from(1u64), 1, 1, cap),
+            Err(ScoringError::InRangeExceedsTotal(_))
+        ));
+        for bad_cap in [U256::ZERO, U256::from(MAX_BPS + 1)] {
+            assert_eq!(
+                density_range_score(U256::from(1u64), U256::from(1u64), 1, 1, bad_cap),
+                Err(ScoringError::InvalidLiquidityShareCap(InvalidLiquidityShareCap { capBps: bad_cap }))
+            );
+        }
+    }
+
+    // ============ Oracle Snapshot Tests ============
+
+    fn snapshot(timestamp: u32, tick_cumulative: i64, seconds_inside: u32) -> CumulativeSnapshot {
+        CumulativeSnapshot {
+            timestamp,
+            tick_cumulative,
+            seconds_inside,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_in_range_time_and_mean_tick() {
+        // In range 1800 of 3600 seconds; the position's share of pool liquidity does not matter
+        let start = snapshot(1_000, 500_000, 7);
+        let end = snapshot(4_600, 500_000 + 3_600 * 120, 7 + 1_800);
+        let stats = range_stats_from_snapshots(&start, &end).unwrap();
+        assert_eq!(stats.in_range_time, 1_800);
+        assert_eq!(stats.elapsed, 3_600);
+        assert_eq!(stats.mean_tick, 120);
+
+        // In range the whole time
+        let end = snapshot(4_600, 500_000, 7 + 3_600);
+        assert_eq!(range_stats_from_snapshots(&start, &end).unwrap().in_range_time, 3_600);
+    }
+
+    #[test]
+    fn test_snapshot_mean_tick_rounds_toward_negative_infinity() {
+        let start = snapshot(0, 0, 0);
+        let mean = |tick_cumulative| {
+            range_stats_from_snapshots(&start, &snapshot(10, tick_cumulative, 0))
+                .unwrap()
+                .mean_tick
+        };
+        assert_eq!(mean(-25), -3);
+        assert_eq!(mean(25), 2);
+        assert_eq!(mean(-20), -2);
+    }
+
+    #[test]
+    fn test_snapshot_mean_tick_bounds() {
+        let start = snapshot(0, 0, 0);
+        let mean = |tick_cumulative| range_stats_from_snapshots(&start, &snapshot(10, tick_cumulative, 0));
+        let max = i64::from(MAX_TICK) * 10;
+        let min = i64::from(MIN_TICK) * 10;
+        assert_eq!(mean(max).unwrap().mean_tick, MAX_TICK);
+        assert_eq!(mean(min).unwrap().mean_tick, MIN_TICK);
+        assert_eq!(mean(max + 10), Err(ScoringError::TickOutOfRange(TickOutOfRange {})));
+        assert_eq!(mean(min - 1), Err(ScoringError::TickOutOfRange(TickOutOfRange {})));
+        // A delta near 2^55 over one second would truncate if cast straight to i32
+        let wide = range_stats_from_snapshots(&start, &snapshot(1, (1i64 << 55) - 1, 0));
+        assert_eq!(wide, Err(ScoringError::TickOutOfRange(TickOutOfRange {})));
+    }
+
+    #[test]
+    fn test_snapshot_wraparound() {
+        let max56 = (1i64 << 55) - 1;
+        let min56 = -(1i64 << 55);
+        // tickCumulative wraps from +2^55 - 1 to -2^55: a +200 move over 10s
+        let start = snapshot(u32::MAX - 4, max56 - 99, 0);
+        let end = snapshot(5, min56 + 100, 0);
+        let stats = range_stats_from_snapshots(&start, &end).unwrap();
+        assert_eq!(stats.elapsed, 10);
+        assert_eq!(stats.mean_tick, 20);
+
+        // secondsInside wraps modulo 2^32
+        let start = snapshot(0, 0, u32::MAX - 19);
+        let end = snapshot(100, 0, 30);
+        assert_eq!(range_stats_from_snapshots(&start, &end).unwrap().in_range_time, 50);
+    }
+
+    #[test]
+    fn test_snapshot_validation() {
+        let start = snapshot(100, 0, 0);
+        assert_eq!(
+            range_stats_from_snapshots(&start, &start),
+            Err(ScoringError::ZeroDuration(ZeroDuration {}))
+        );
+        let bad_tick = snapshot(200, 1i64 << 55, 0);
+        assert_eq!(
+            range_stats_from_snapshots(&start, &bad_tick),
+            Err(ScoringError::CumulativeOutOfRange(CumulativeOutOfRange {}))
+        );
+        // More seconds inside than elapsed
+        let end = snapshot(200, 0, 101);
+        assert!(matches!(
+            range_stats_from_snapshots(&start, &end),
+            Err(ScoringError::InRangeExceedsTotal(_))
+        ));
+    }
+
+    // ============ Determine Winner Tests ============
+
+    #[test]
+    fn test_winner_a_higher() {
+        assert_eq!(winner(U256::from(100u64), U256::from(50u64)), 1);
+    }
+
+    #[test]
+    fn test_winner_b_higher() {
+        assert_eq!(winner(U256::from(50u64), U256::from(100u64)), 2);
+    }
+
+    #[test]
+    fn test_winner_tie_goes_to_a() {
+        assert_eq!(winner(U256::from(100u64), U256::from(100u64)), 1);
+    }
+
+    #[test]
+    fn test_winner_both_zero() {
+        assert_eq!(winner(U256::ZERO, U256::ZERO), 1);
+    }
+
+    // ============ Outcome (Draw-Aware) Tests ============
+
+    #[test]
+    fn test_outcome_clear_winners() {
+        let tol = U256::from(DEFAULT_DRAW_TOLERANCE_BPS);
+        assert_eq!(outcome(U256::from(E18), U256::from(E18 / 2), tol), OUTCOME_PLAYER_A);
+        assert_eq!(outcome(U256::from(E18 / 2), U256::from(E18), tol), OUTCOME_PLAYER_B);
+    }
+
+    #[test]
+    fn test_outcome_exact_tie_is_draw() {
+        let tol = U256::from(DEFAULT_DRAW_TOLERANCE_BPS);
+        assert_eq!(outcome(U256::from(E18), U256::from(E18), tol), OUTCOME_DRAW);
+        assert_eq!(outcome(U256::ZERO, U256::ZERO, tol), OUTCOME_DRAW);
+    }
+
+    #[test]
+    fn test_outcome_within_tolerance_is_draw() {
+        // 0.1% tolerance: 1e18 vs 0.999e18 is exactly on the boundary
+        let tol = U256::from(DEFAULT_DRAW_TOLERANCE_BPS);
+        let a = U256::from(E18);
+        let b = U256::from(E18 - E18 / 1000);
+        assert_eq!(outcome(a, b, tol), OUTCOME_DRAW);
+        assert_eq!(outcome(b, a, tol), OUTCOME_DRAW);
+        // One unit past the boundary is a win
+        let b = b - U256::from(1u64);
+        assert_eq!(outcome(a, b, tol), OUTCOME_PLAYER_A);
+        assert_eq!(outcome(b, a, tol), OUTCOME_PLAYER_B);
+    }
+
+    #[test]
+    fn test_outcome_zero_tolerance_only_exact_draw() {
+        let a = U256::from(E18);
+        assert_eq!(outcome(a, a, U256::ZERO), OUTCOME_DRAW);
+        assert_eq!(outcome(a, a - U256::from(1u64), U256::ZERO), OUTCOME_PLAYER_A);
+    }
+
+    #[test]
+    fn test_outcome_one_zero_score_not_draw() {
+        let tol = U256::from(MAX_DRAW_TOLERANCE_BPS);
+        assert_eq!(outcome(U256::ZERO, U256::from(1u64), tol), OUTCOME_PLAYER_B);
+    }
+
+    #[test]
+    fn test_outcome_large_scores_do_not_saturate() {
+        let tol = U256::from(DEFAULT_DRAW_TOLERANCE_BPS);
+        assert_eq!(outcome(U256::MAX, U256::ZERO, tol), OUTCOME_PLAYER_A);
+        assert_eq!(outcome(U256::ZERO, U256::MAX, tol), OUTCOME_PLAYER_B);
+        assert_eq!(outcome(U256::MAX, U256::MAX / U256::from(2u64), tol), OUTCOME_PLAYER_A);
+        // Still a draw within tolerance at the top of the range
+        let close = U256::MAX - U256::MAX / U256::from(MAX_BPS);
+        assert_eq!(outcome(U256::MAX, close, tol), OUTCOME_DRAW);
+        assert_eq!(outcome(U256::MAX, close, U256::ZERO), OUTCOME_PLAYER_A);
+    }
+
+    // ============ Tie-Break Tests ============
+
+    fn metrics(tick_width: u64, value_usd: u64, joined_at: u64) -> TieBreakMetrics {
+        TieBreakMetrics {
+            tick_width: U256::from(tick_width),
+            value_usd: U256::from(value_usd),
+            joined_at: U256::from(joined_at),
+        }
+    }
+
+    #[test]
+    fn test_break_tie_narrower_tick_width() {
+        let a = metrics(600, 1000, 100);
+        let b = metrics(120, 5000, 50);
+        assert_eq!(break_tie(&a, &b, U256::ZERO), (OUTCOME_PLAYER_B, TIEBREAK_TICK_WIDTH));
+        assert_eq!(break_tie(&b, &a, U256::ZERO), (OUTCOME_PLAYER_A, TIEBREAK_TICK_WIDTH));
+    }
+
+    #[test]
+    fn test_break_tie_lower_value() {
+        let a = metrics(120, 1000, 100);
+        let b = metrics(120, 1500, 50);
+        assert_eq!(break_tie(&a, &b, U256::ZERO), (OUTCOME_PLAYER_A, TIEBREAK_USD_VALUE));
+    }
+
+    #[test]
+    fn test_break_tie_earlier_join() {
+        let a = metrics(120, 1000, 100);
+        let b = metrics(120, 1000, 50);
+        assert_eq!(break_tie(&a, &b, U256::ZERO), (OUTCOME_PLAYER_B, TIEBREAK_JOIN_TIME));
+    }
+
+    #[test]
+    fn test_break_tie_coin_flip_deterministic() {
+        let a = metrics(120, 1000, 100);
+        let seed = U256::from(42u64);
+        let first = break_tie(&a, &a, seed);
+        assert_eq!(first.1, TIEBREAK_COIN_FLIP);
+        assert_eq!(break_tie(&a, &a, seed), first);
+    }
+
+    #[test]
+    fn test_break_tie_coin_flip_reaches_both_sides() {
+        let a = metrics(120, 1000, 100);
+        let winners: Vec<u8> = (0u64..32)
+            .map(|seed| break_tie(&a, &a, U256::from(seed)).0)
+            .collect();
+        assert!(winners.contains(&OUTCOME_PLAYER_A));
+        assert!(winners.contains(&OUTCOME_PLAYER_B));
+    }
+
+    #[test]
+    fn test_draw_tolerance_bounds() {
+        assert!(is_valid_draw_tolerance(U256::ZERO));
+        assert!(is_valid_draw_tolerance(U256::from(MAX_DRAW_TOLERANCE_BPS)));
+        assert!(!is_valid_draw_tolerance(U256::from(MAX_DRAW_TOLERANCE_BPS + 1)));
+    }
+
+    #[test]
+    fn test_draw_rewards_split_evenly() {
+        let (a, b, r) = draw_rewards(U256::from(10000u64), U256::from(100u64)).unwrap();
+        assert_eq!(r, U256::from(100u64));
+        assert_eq!(a, U256::from(4950u64));
+        assert_eq!(b, U256::from(4950u64));
+    }
+
+    #[test]
+    fn test_draw_rewards_odd_dust_to_resolver() {
+        let total = U256::from(10001u64);
+        let (a, b, r) = draw_rewards(total, U256::ZERO).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(r, U256::from(1u64));
+        assert_eq!(a + b + r, total);
+    }
+
+    // ============ Calculate Rewards Tests ============
+
+    #[test]
+    fn test_rewards_standard_1_percent() {
+        let (w, r) = rewards(U256::from(10000u64), U256::from(100u64)).unwrap();
+        assert_eq!(r, U256::from(100u64));
+        assert_eq!(w, U256::from(9900u64));
+    }
+
+    #[test]
+    fn test_rewards_10_percent() {
+        let (w, r) = rewards(U256::from(10000u64), U256::from(1000u64)).unwrap();
+        assert_eq!(r, U256::from(1000u64));
+        assert_eq!(w, U256::from(9000u64));
+    }
+
+    #[test]
+    fn test_rewards_zero_fees() {
+        let (w, r) = rewards(U256::ZERO, U256::from(100u64)).unwrap();
+        assert_eq!(w, U256::ZERO);
+        assert_eq!(r, U256::ZERO);
+    }
+
+    #[test]
+    fn test_rewards_zero_resolver_bps() {
+        let (w, r) = rewards(U256::from(10000u64), U256::ZERO).unwrap();
+        assert_eq!(w, U256::from(10000u64));
+        assert_eq!(r, U256::ZERO);
+    }
+
+    #[test]
     fn test_rewards_full_bps() {
-        let (w, r) = rewards(U256::from(10000u64), U256::from(10000u64));
+        let (w, r) = rewards(U256::from(10000u64), U256::from(10000u64)).unwrap();
         assert_eq!(w, U256::ZERO);
         assert_eq!(r, U256::from(10000u64));
     }
@@ -337,30 +3325,580 @@ mod tests {
     #[test]
     fn test_rewards_conservation() {
         let total = U256::from(123_456_789u64);
-        let (w, r) = rewards(total, U256::from(100u64));
+        let (w, r) = rewards(total, U256::from(100u64)).unwrap();
         assert_eq!(w + r, total);
     }
 
+    // ============ Pair Rewards Tests ============
+
+    #[test]
+    fn test_pair_rewards_basic() {
+        let split = pair_rewards(
+            U256::from(10000u64),
+            U256::from(50000u64),
+            U256::from(100u64),
+            U256::from(500u64),
+        )
+        .unwrap();
+        assert_eq!(split.resolver0, U256::from(100u64));
+        assert_eq!(split.protocol0, U256::from(500u64));
+        assert_eq!(split.winner0, U256::from(9400u64));
+        assert_eq!(split.resolver1, U256::from(500u64));
+        assert_eq!(split.protocol1, U256::from(2500u64));
+        assert_eq!(split.winner1, U256::from(47000u64));
+    }
+
+    #[test]
+    fn test_pair_rewards_conserves_each_token() {
+        let amounts = [0u64, 1, 99, 10_001, 123_456_789, 999_999_999_999];
+        for fees0 in amounts {
+            for fees1 in amounts {
+                let split = pair_rewards(
+                    U256::from(fees0),
+                    U256::from(fees1),
+                    U256::from(137u64),
+                    U256::from(251u64),
+                )
+                .unwrap();
+                assert_eq!(split.winner0 + split.resolver0 + split.protocol0, U256::from(fees0));
+                assert_eq!(split.winner1 + split.resolver1 + split.protocol1, U256::from(fees1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pair_rewards_dust_to_winner_per_token() {
+        let split = pair_rewards(
+            U256::from(199u64),
+            U256::from(99u64),
+            U256::from(100u64),
+            U256::from(100u64),
+        )
+        .unwrap();
+        assert_eq!(split.winner0, U256::from(197u64));
+        assert_eq!(split.resolver0, U256::from(1u64));
+        assert_eq!(split.protocol0, U256::from(1u64));
+        // 1% of 99 rounds down to zero for both cuts
+        assert_eq!(split.winner1, U256::from(99u64));
+        assert_eq!(split.resolver1 + split.protocol1, U256::ZERO);
+    }
+
+    #[test]
+    fn test_pair_rewards_one_token_empty() {
+        let split = pair_rewards(U256::ZERO, U256::from(10000u64), U256::from(100u64), U256::ZERO).unwrap();
+        assert_eq!(split.winner0 + split.resolver0 + split.protocol0, U256::ZERO);
+        assert_eq!(split.winner1, U256::from(9900u64));
+    }
+
+    #[test]
+    fn test_pair_rewards_rejects_bps_over_max() {
+        let result = pair_rewards(
+            U256::from(10000u64),
+            U256::from(10000u64),
+            U256::from(6000u64),
+            U256::from(5000u64),
+        );
+        assert!(matches!(result, Err(ScoringError::RewardBpsOutOfRange(_))));
+    }
+
+    // ============ Proportional Rewards Tests ============
+
+    #[test]
+    fn test_proportional_rewards_basic() {
+        // 3:1 score ratio → 75% / 25%
+        let (a, b) =
+            proportional_rewards(U256::from(3 * E18), U256::from(E18), U256::from(10000u64)).unwrap();
+        assert_eq!(a, U256::from(7500u64));
+        assert_eq!(b, U256::from(2500u64));
+    }
+
+    #[test]
+    fn test_proportional_rewards_loser_zero_score() {
+        let (a, b) = proportional_rewards(U256::ZERO, U256::from(E18), U256::from(10000u64)).unwrap();
+        assert_eq!(a, U256::ZERO);
+        assert_eq!(b, U256::from(10000u64));
+    }
+
+    #[test]
+    fn test_proportional_rewards_both_zero_split_evenly() {
+        let (a, b) = proportional_rewards(U256::ZERO, U256::ZERO, U256::from(10000u64)).unwrap();
+        assert_eq!(a, U256::from(5000u64));
+        assert_eq!(b, U256::from(5000u64));
+    }
+
+    #[test]
+    fn test_proportional_rewards_dust_to_higher_score() {
+        // 1:2 split of 100 → 33 / 66 with 1 unit of dust to B
+        let (a, b) = proportional_rewards(U256::from(1u64), U256::from(2u64), U256::from(100u64)).unwrap();
+        assert_eq!(a, U256::from(33u64));
+        assert_eq!(b, U256::from(67u64));
+    }
+
+    #[test]
+    fn test_proportional_rewards_conservation() {
+        let scores = [(1u64, 1u64), (1, 2), (7, 13), (E18, E18 / 3), (0, 5), (0, 0)];
+        for (score_a, score_b) in scores {
+            for total in [0u64, 1, 3, 101, 123_456_789] {
+                let (a, b) =
+                    proportional_rewards(U256::from(score_a), U256::from(score_b), U256::from(total))
+                        .unwrap();
+                assert_eq!(a + b, U256::from(total), "scores ({score_a}, {score_b}), total {total}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_proportional_rewards_large_scores() {
+        let half = U256::MAX / U256::from(2u64);
+        let total = U256::from(1_000_001u64);
+
+        let (a, b) = proportional_rewards(half, half + U256::from(1u64), total).unwrap();
+        assert_eq!(a, U256::from(500_000u64));
+        assert_eq!(b, U256::from(500_001u64));
+
+        let (a, b) = proportional_rewards(U256::MAX, U256::MAX, U256::MAX).unwrap();
+        assert_eq!(a, U256::MAX / U256::from(2u64) + U256::from(1u64));
+        assert_eq!(b, U256::MAX / U256::from(2u64));
+        assert_eq!(a + b, U256::MAX);
+
+        let (a, b) = proportional_rewards(U256::MAX, U256::from(1u64), total).unwrap();
+        assert_eq!((a, b), (total, U256::ZERO));
+    }
+
+    // ============ Three-Way Rewards Tests ============
+
+    #[test]
+    fn test_rewards_v2_standard_split() {
+        let (w, r, p) = rewards_v2(
+            U256::from(10000u64),
+            U256::from(100u64),
+            U256::from(500u64),
+            U256::from(1000u64),
+        )
+        .unwrap();
+        assert_eq!(r, U256::from(100u64));
+        assert_eq!(p, U256::from(500u64));
+        assert_eq!(w, U256::from(9400u64));
+    }
+
+    #[test]
+    fn test_rewards_v2_matches_v1_without_protocol() {
+        let total = U256::from(123_456_789u64);
+        let (w, r, p) = rewards_v2(total, U256::from(100u64), U256::ZERO, U256::from(MAX_BPS)).unwrap();
+        assert_eq!((w, r), rewards(total, U256::from(100u64)).unwrap());
+        assert_eq!(p, U256::ZERO);
+    }
+
+    #[test]
+    fn test_rewards_v2_conservation() {
+        for total in [0u64, 1, 7, 9_999, 123_456_789, 1_000_000_007] {
+            let total = U256::from(total);
+            let (w, r, p) = rewards_v2(
+                total,
+                U256::from(333u64),
+                U256::from(777u64),
+                U256::from(2000u64),
+            )
+            .unwrap();
+            assert_eq!(w + r + p, total);
+        }
+    }
+
+    #[test]
+    fn test_rewards_v2_dust_goes_to_winner() {
+        // 1% and 1% of 199 both round down to 1, leaving 197 for the winner
+        let (w, r, p) = rewards_v2(
+            U256::from(199u64),
+            U256::from(100u64),
+            U256::from(100u64),
+            U256::from(MAX_BPS),
+        )
+        .unwrap();
+        assert_eq!((w, r, p), (U256::from(197u64), U256::from(1u64), U256::from(1u64)));
+    }
+
+    #[test]
+    fn test_rewards_v2_rejects_combined_over_max() {
+        let result = rewards_v2(
+            U256::from(10000u64),
+            U256::from(600u64),
+            U256::from(500u64),
+            U256::from(1000u64),
+        );
+        assert!(matches!(result, Err(ScoringError::RewardBpsOutOfRange(_))));
+    }
+
+    #[test]
+    fn test_rewards_v2_rejects_max_over_full_bps() {
+        let result = rewards_v2(
+            U256::from(10000u64),
+            U256::from(100u64),
+            U256::from(100u64),
+            U256::from(MAX_BPS + 1),
+        );
+        assert!(matches!(result, Err(ScoringError::RewardBpsOutOfRange(_))));
+    }
+
+    #[test]
+    fn test_rewards_v2_rejects_overflowing_bps() {
+        let result = rewards_v2(U256::from(10000u64), U256::MAX, U256::from(1u64), U256::from(MAX_BPS));
+        assert!(matches!(result, Err(ScoringError::RewardBpsOutOfRange(_))));
+    }
+
+    #[test]
+    fn test_rewards_v2_full_combined_bps() {
+        let (w, r, p) = rewards_v2(
+            U256::from(10000u64),
+            U256::from(4000u64),
+            U256::from(6000u64),
+            U256::from(MAX_BPS),
+        )
+        .unwrap();
+        assert_eq!(w, U256::ZERO);
+        assert_eq!(r + p, U256::from(10000u64));
+    }
+
+    // ============ Batch Scoring Tests ============
+
+    fn player(metric: u64, lp_value_usd: u64, tick_distance: u64, dex_weight_bps: u64) -> PlayerInput {
+        PlayerInput {
+            metric: U256::from(metric),
+            lp_value_usd: U256::from(lp_value_usd),
+            tick_distance: U256::from(tick_distance),
+            dex_weight_bps: U256::from(dex_weight_bps),
+        }
+    }
+
+    fn battle(battle_type: u8, player_a: PlayerInput, player_b: PlayerInput) -> BattleInput {
+        BattleInput {
+            battle_type,
+            duration: U256::from(3600u64),
+            player_a,
+            player_b,
+            total_fees: U256::from(10_000u64),
+            resolver_bps: U256::from(100u64),
+        }
+    }
+
+    fn default_params() -> (U256, U256, U256) {
+        (
+            U256::from(TIGHT_RANGE_THRESHOLD),
+            U256::from(TIGHT_RANGE_BONUS),
+            U256::from(DEFAULT_DRAW_TOLERANCE_BPS),
+        )
+    }
+
+    #[test]
+    fn test_score_battle_range_matches_individual_calls() {
+        let (threshold, bonus, tol) = default_params();
+        let a = player(3600, 0, 200, 10_000);
+        let b = player(2700, 0, 0, 12_000);
+        let result = score_battle(&battle(BATTLE_TYPE_RANGE, a, b), threshold, bonus, tol).unwrap();
+
+        let duration = U256::from(3600u64);
+        let raw_a = range_score(a.metric, duration, a.tick_distance).unwrap();
+        let raw_b = range_score(b.metric, duration, b.tick_distance).unwrap();
+        let score_a = normalize_cross_dex(raw_a, a.dex_weight_bps).unwrap();
+        let score_b = normalize_cross_dex(raw_b, b.dex_weight_bps).unwrap();
+        let (winner_amount, resolver_amount) = rewards(U256::from(10_000u64), U256::from(100u64)).unwrap();
+        assert_eq!(
+            result,
+            BattleResult {
+                score_a,
+                score_b,
+                winner: outcome(score_a, score_b, tol),
+                winner_amount,
+                resolver_amount,
+            }
+        );
+        // 0.75 with the full 20% bonus at 1.2x (1.08) beats 1.0 at 1.0x
+        assert_eq!(result.winner, OUTCOME_PLAYER_B);
+        assert_eq!(result.winner_amount, U256::from(9_900u64));
+        assert_eq!(result.resolver_amount, U256::from(100u64));
+    }
+
+    #[test]
+    fn test_score_battle_fee() {
+        let (threshold, bonus, tol) = default_params();
+        // Same yield, but A's DEX weight is lower
+        let a = player(100, 10_000, 0, 9_000);
+        let b = player(100, 10_000, 0, 10_000);
+        let result = score_battle(&battle(BATTLE_TYPE_FEE, a, b), threshold, bonus, tol).unwrap();
+        let raw = fee_score(U256::from(100u64), U256::from(10_000u64), U256::from(3600u64)).unwrap();
+        assert_eq!(result.score_b, raw);
+        assert_eq!(result.score_a, normalize_cross_dex(raw, U256::from(9_000u64)).unwrap());
+        assert_eq!(result.winner, OUTCOME_PLAYER_B);
+    }
+
+    #[test]
+    fn test_score_battle_draw_splits_the_pot() {
+        let (threshold, bonus, tol) = default_params();
+        // Equal scores: neither side takes the whole pot
+        let a = player(1800, 0, 500, 10_000);
+        let result = score_battle(&battle(BATTLE_TYPE_RANGE, a, a), threshold, bonus, tol).unwrap();
+        assert_eq!(result.score_a, result.score_b);
+        assert_eq!(result.winner, OUTCOME_DRAW);
+        let (half, _, resolver) = draw_rewards(U256::from(10_000u64), U256::from(100u64)).unwrap();
+        assert_eq!((result.winner_amount, result.resolver_amount), (half, resolver));
+        assert_eq!(result.winner_amount, U256::from(4_950u64));
+        assert_eq!(result.resolver_amount, U256::from(100u64));
+
+        // An odd pot leaves its extra unit with the resolver, so nothing is paid twice
+        let odd = BattleInput { total_fees: U256::from(10_001u64), ..battle(BATTLE_TYPE_RANGE, a, a) };
+        let result = score_battle(&odd, threshold, bonus, tol).unwrap();
+        assert_eq!(result.winner_amount * U256::from(2u64) + result.resolver_amount, U256::from(10_001u64));
+    }
+
+    #[test]
+    fn test_score_battle_draw_within_tolerance() {
+        let (threshold, bonus, tol) = default_params();
+        // Fee yields 0.05% apart are a draw
+        let a = player(10_000, 1_000_000, 0, 10_000);
+        let b = player(9_995, 1_000_000, 0, 10_000);
+        let result = score_battle(&battle(BATTLE_TYPE_FEE, a, b), threshold, bonus, tol).unwrap();
+        assert!(result.score_a > result.score_b);
+        assert_eq!(result.winner, OUTCOME_DRAW);
+        assert_eq!(result.winner_amount, U256::from(4_950u64));
+
+        // Without a tolerance the higher score wins outright and takes the pot
+        let strict = score_battle(&battle(BATTLE_TYPE_FEE, a, b), threshold, bonus, U256::ZERO).unwrap();
+        assert_eq!(strict.winner, OUTCOME_PLAYER_A);
+        assert_eq!(strict.winner_amount, U256::from(9_900u64));
+    }
+
+    #[test]
+    fn test_score_battle_errors() {
+        let (threshold, bonus, tol) = default_params();
+        let a = player(1800, 0, 500, 10_000);
+        assert_eq!(
+            score_battle(&battle(2, a, a), threshold, bonus, tol),
+            Err(ScoringError::UnknownBattleType(UnknownBattleType { battleType: 2 }))
+        );
+        // Fee battles still reject a zero LP value
+        assert_eq!(
+            score_battle(&battle(BATTLE_TYPE_FEE, a, a), threshold, bonus, tol),
+            Err(ScoringError::ZeroLpValue(ZeroLpValue {}))
+        );
+        // Range battles still reject more in-range time than the duration
+        let b = player(3601, 0, 500, 10_000);
+        assert!(matches!(
+            score_battle(&battle(BATTLE_TYPE_RANGE, a, b), threshold, bonus, tol),
+            Err(ScoringError::InRangeExceedsTotal(_))
+        ));
+    }
+
+    // ============ Ranked Battle Tests ============
+
+    fn scores(values: &[u64]) -> Vec<U256> {
+        values.iter().map(|&v| U256::from(v)).collect()
+    }
+
+    fn curve(values: &[u64]) -> Vec<U256> {
+        scores(values)
+    }
+
+    #[test]
+    fn test_rank_distinct_scores() {
+        let ranking = rank(&scores(&[300, 900, 100, 500]), U256::ZERO).unwrap();
+        assert_eq!(ranking.order, vec![1, 3, 0, 2]);
+        assert_eq!(ranking.placements, vec![3, 1, 4, 2]);
+    }
+
+    #[test]
+    fn test_rank_equal_scores_are_stable_and_share_place() {
+        let ranking = rank(&scores(&[500, 900, 500, 100]), U256::ZERO).unwrap();
+        assert_eq!(ranking.order, vec![1, 0, 2, 3]);
+        assert_eq!(ranking.placements, vec![2, 1, 2, 4]);
+    }
+
+    #[test]
+    fn test_rank_draw_groups_use_tolerance() {
+        // 10 bps: 10000 and 9990 draw, 9985 is more than 10 bps below the group leader
+        let ranking = rank(&scores(&[9_985, 10_000, 9_990]), U256::from(10u64)).unwrap();
+        assert_eq!(ranking.order, vec![1, 2, 0]);
+        assert_eq!(ranking.placements, vec![3, 1, 1]);
+        // Without tolerance every player has their own place
+        let ranking = rank(&scores(&[9_985, 10_000, 9_990]), U256::ZERO).unwrap();
+        assert_eq!(ranking.placements, vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_rank_large_scores_keep_separate_places() {
+        let huge = [U256::MAX, U256::MAX / U256::from(2u64), U256::from(1u64)];
+        let ranking = rank(&huge, U256::from(DEFAULT_DRAW_TOLERANCE_BPS)).unwrap();
+        assert_eq!(ranking.order, vec![0, 1, 2]);
+        assert_eq!(ranking.placements, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rank_player_count_bounds() {
+        assert!(rank(&scores(&[1, 2]), U256::ZERO).is_ok());
+        assert!(rank(&scores(&[1; MAX_RANKED_PLAYERS]), U256::ZERO).is_ok());
+        assert_eq!(
+            rank(&scores(&[1]), U256::ZERO),
+            Err(ScoringError::InvalidPlayerCount(InvalidPlayerCount { count: U256::from(1u64) }))
+        );
+        assert_eq!(
+            rank(&scores(&[1; MAX_RANKED_PLAYERS + 1]), U256::ZERO),
+            Err(ScoringError::InvalidPlayerCount(InvalidPlayerCount { count: U256::from(9u64) }))
+        );
+    }
+
+    #[test]
+    fn test_placements_validation() {
+        assert!(is_valid_placements(&[1, 2, 3]));
+        assert!(is_valid_placements(&[3, 1, 1]));
+        assert!(is_valid_placements(&[2, 1, 2, 4]));
+        assert!(is_valid_placements(&[1, 1, 1]));
+        assert!(!is_valid_placements(&[1, 2, 2, 3]));
+        assert!(!is_valid_placements(&[0, 1, 2]));
+        assert!(!is_valid_placements(&[2, 3]));
+    }
+
+    #[test]
+    fn test_ranked_rewards_60_30_10() {
+        let amounts = ranked_rewards(U256::from(1000u64), &curve(&[6000, 3000, 1000]), &[2, 1, 3]).unwrap();
+        assert_eq!(amounts, scores(&[300, 600, 100]));
+    }
+
+    #[test]
+    fn test_ranked_rewards_shorter_curve_than_players() {
+        let amounts = ranked_rewards(U256::from(1000u64), &curve(&[7000, 3000]), &[4, 3, 1, 2]).unwrap();
+        assert_eq!(amounts, scores(&[0, 0, 700, 300]));
+    }
+
+    #[test]
+    fn test_ranked_rewards_draw_group_splits_places() {
+        // Two players tied for 1st share 60% + 30%; 3rd keeps 10%
+        let amounts = ranked_rewards(U256::from(1000u64), &curve(&[6000, 3000, 1000]), &[1, 3, 1]).unwrap();
+        assert_eq!(amounts, scores(&[450, 100, 450]));
+        // Two players tied for 2nd share 30% + 10%
+        let amounts = ranked_rewards(U256::from(1000u64), &curve(&[6000, 3000, 1000]), &[2, 1, 2]).unwrap();
+        assert_eq!(amounts, scores(&[200, 600, 200]));
+    }
+
+    #[test]
+    fn test_ranked_rewards_dust_to_first_place() {
+        let total = U256::from(1001u64);
+        let amounts = ranked_rewards(total, &curve(&[5000, 5000]), &[2, 1, 2]).unwrap();
+        // 1st gets 500 + 1 dust, the 2nd-place draw group splits 500 evenly
+        assert_eq!(amounts, scores(&[250, 501, 250]));
+        assert_eq!(amounts.iter().fold(U256::ZERO, |sum, &a| sum + a), total);
+    }
+
+    #[test]
+    fn test_ranked_rewards_invalid_inputs() {
+        let total = U256::from(1000u64);
+        assert_eq!(
+            ranked_rewards(total, &curve(&[6000, 3000]), &[1, 2, 3]),
+            Err(ScoringError::InvalidPayoutCurve(InvalidPayoutCurve {
+                length: U256::from(2u64),
+                totalBps: U256::from(9000u64),
+            }))
+        );
+        assert!(matches!(
+            ranked_rewards(total, &curve(&[5000, 3000, 1000, 1000]), &[1, 2, 3]),
+            Err(ScoringError::InvalidPayoutCurve(_))
+        ));
+        assert!(matches!(ranked_rewards(total, &[], &[1, 2]), Err(ScoringError::InvalidPayoutCurve(_))));
+        assert!(matches!(
+            ranked_rewards(total, &[U256::MAX, U256::from(1u64)], &[1, 2]),
+            Err(ScoringError::InvalidPayoutCurve(_))
+        ));
+        assert_eq!(
+            ranked_rewards(total, &curve(&[10000]), &[1, 3]),
+            Err(ScoringError::InvalidPlacements(InvalidPlacements {}))
+        );
+        assert!(matches!(
+            ranked_rewards(total, &curve(&[10000]), &[1]),
+            Err(ScoringError::InvalidPlayerCount(_))
+        ));
+    }
+
     // ============ Cross-DEX Normalization Tests ============
 
     #[test]
     fn test_normalize_equal_weights() {
-        // Both DEXes at 10000 BPS (1.0x) → no change
+        // Both DEXes are registered at 10000 BPS (1.0x) by the constructor → no change
         let score = U256::from(1_000_000u64);
-        assert_eq!(normalize_cross_dex(score, 0), score); // V4
-        assert_eq!(normalize_cross_dex(score, 1), score); // Camelot
+        for dex_type in [DEX_UNISWAP_V4, DEX_CAMELOT_V3] {
+            let weight = active_dex_weight(dex_type, true, true, U256::from(DEFAULT_DEX_WEIGHT_BPS)).unwrap();
+            assert_eq!(normalize_cross_dex(score, weight).unwrap(), score, "dex {dex_type}");
+        }
     }
 
     #[test]
     fn test_normalize_zero_score() {
-        assert_eq!(normalize_cross_dex(U256::ZERO, 0), U256::ZERO);
-        assert_eq!(normalize_cross_dex(U256::ZERO, 1), U256::ZERO);
+        assert_eq!(normalize_cross_dex(U256::ZERO, U256::from(11_000u64)).unwrap(), U256::ZERO);
+        assert_eq!(normalize_cross_dex(U256::ZERO, U256::from(9_000u64)).unwrap(), U256::ZERO);
     }
 
     #[test]
     fn test_normalize_unknown_dex() {
-        // Unknown DEX type gets 1.0x (no adjustment)
-        let score = U256::from(5000u64);
-        assert_eq!(normalize_cross_dex(score, 255), score);
+        // Unregistered DEX types are rejected instead of passing through at 1.0x
+        assert_eq!(
+            active_dex_weight(7, false, false, U256::ZERO),
+            Err(ScoringError::UnknownDex(UnknownDex { dexType: 7 }))
+        );
+    }
+
+    #[test]
+    fn test_active_dex_weight_disabled() {
+        assert_eq!(
+            active_dex_weight(1, true, false, U256::from(DEFAULT_DEX_WEIGHT_BPS)),
+            Err(ScoringError::DexDisabled(DexDisabled { dexType: 1 }))
+        );
+    }
+
+    #[test]
+    fn test_active_dex_weight_enabled() {
+        let weight = U256::from(12_500u64);
+        assert_eq!(active_dex_weight(0, true, true, weight), Ok(weight));
+    }
+
+    #[test]
+    fn test_normalize_custom_weights() {
+        let score = U256::from(1_000_000u64);
+        assert_eq!(normalize_cross_dex(score, U256::from(11_000u64)).unwrap(), U256::from(1_100_000u64));
+        assert_eq!(normalize_cross_dex(score, U256::from(9_000u64)).unwrap(), U256::from(900_000u64));
+    }
+
+    #[test]
+    fn test_pinned_dex_weight() {
+        let weight = U256::from(9_000u64);
+        assert_eq!(pinned_dex_weight(0, weight), Ok(weight));
+        assert_eq!(
+            pinned_dex_weight(2, U256::ZERO),
+            Err(ScoringError::DexNotInParamSet(DexNotInParamSet { dexType: 2 }))
+        );
+    }
+
+    // ============ Parameter Set Tests ============
+
+    #[test]
+    fn test_resolver_bps_limits_validation() {
+        assert!(is_valid_resolver_bps_limits(U256::ZERO, U256::from(MAX_BPS)));
+        assert!(is_valid_resolver_bps_limits(U256::from(100u64), U256::from(100u64)));
+        assert!(!is_valid_resolver_bps_limits(U256::from(200u64), U256::from(100u64)));
+        assert!(!is_valid_resolver_bps_limits(U256::ZERO, U256::from(MAX_BPS + 1)));
+    }
+
+    #[test]
+    fn test_resolver_bps_within_limits() {
+        let (min, max) = (U256::from(50u64), U256::from(500u64));
+        assert!(resolver_bps_within_limits(U256::from(50u64), min, max));
+        assert!(resolver_bps_within_limits(U256::from(500u64), min, max));
+        assert!(!resolver_bps_within_limits(U256::from(49u64), min, max));
+        assert!(!resolver_bps_within_limits(U256::from(501u64), min, max));
+    }
+
+    #[test]
+    fn test_dex_weight_bounds() {
+        assert!(is_valid_dex_weight(U256::from(MIN_DEX_WEIGHT_BPS)));
+        assert!(is_valid_dex_weight(U256::from(MAX_DEX_WEIGHT_BPS)));
+        assert!(is_valid_dex_weight(U256::from(DEFAULT_DEX_WEIGHT_BPS)));
+        assert!(!is_valid_dex_weight(U256::from(MIN_DEX_WEIGHT_BPS - 1)));
+        assert!(!is_valid_dex_weight(U256::from(MAX_DEX_WEIGHT_BPS + 1)));
+        assert!(!is_valid_dex_weight(U256::ZERO));
     }
 }
@@ -0,0 +1,570 @@
+//! Fixed-point math shared by the LP BattleVault Stylus contracts.
+//!
+//! Every scaled multiply/divide goes through [`mul_div`], which keeps the full
+//! 512-bit intermediate product so `a * b / d` only fails when the *result*
+//! does not fit in 256 bits. Rounding direction is always explicit.
+//!
+//! [`Wad`] (18 decimals) and [`Ray`] (27 decimals) wrap `U256` for fixed-point
+//! values, and [`ln_wad`], [`exp_wad`] and [`pow_wad`] provide the
+//! transcendental functions on WAD-scaled inputs. [`tick_math`] ports the
+//! Uniswap V3/V4 tick and liquidity-amount math.
+#![cfg_attr(not(test), no_std)]
+
+use alloy_primitives::{ruint::UintTryFrom, I256, U256, U512};
+
+pub mod tick_math;
+
+// ============ Constants ============
+
+/// 1.0 in WAD precision (18 decimals)
+pub const WAD: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+
+/// 1.0 in RAY precision (27 decimals)
+pub const RAY: U256 = U256::from_limbs([0x9fd0_803c_e800_0000, 0x33_b2e3c, 0, 0]);
+
+/// RAY / WAD
+const WAD_TO_RAY: U256 = U256::from_limbs([1_000_000_000, 0, 0, 0]);
+
+/// log2(e) in WAD precision
+const LOG2_E_WAD: u64 = 1_442_695_040_888_963_407;
+
+/// ln(2) with 36 decimals, used for exp range reduction
+const LN2_E36: u128 = 693_147_180_559_945_309_417_232_121_458_176_568;
+
+/// 1.0 with 36 decimals
+const E36: u128 = 1_000_000_000_000_000_000_000_000_000_000_000_000;
+
+/// exp(x) overflows a WAD-scaled U256 above ~135.999e18
+const EXP_MAX_INPUT: i128 = 136_000_000_000_000_000_000;
+
+/// exp(x) is below 1 wei (rounds to zero) under ln(1e-18) ~ -41.4465e18
+const EXP_MIN_INPUT: i128 = -41_446_531_673_892_822_313;
+
+// ============ Types ============
+
+/// Rounding direction for divisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round toward zero (floor for unsigned values)
+    Down,
+    /// Round away from zero (ceil for unsigned values)
+    Up,
+}
+
+/// Failure modes of the fixed-point operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    /// The result does not fit in 256 bits
+    Overflow,
+    /// A denominator was zero
+    DivisionByZero,
+    /// The function is not defined for the input (e.g. ln(0))
+    Undefined,
+    /// A tick is outside [MIN_TICK, MAX_TICK]
+    TickOutOfRange,
+}
+
+// ============ mulDiv ============
+
+/// Computes `a * b / denominator` with a 512-bit intermediate product.
+pub fn mul_div(a: U256, b: U256, denominator: U256, rounding: Rounding) -> Result<U256, MathError> {
+    if denominator.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    let product: U512 = a.widening_mul(b);
+    let (quotient, remainder) = product.div_rem(U512::from(denominator));
+    let quotient = if rounding == Rounding::Up && !remainder.is_zero() {
+        quotient + U512::from(1u64)
+    } else {
+        quotient
+    };
+    U256::uint_try_from(quotient).map_err(|_| MathError::Overflow)
+}
+
+/// `a * b / denominator`, rounded down.
+pub fn mul_div_down(a: U256, b: U256, denominator: U256) -> Result<U256, MathError> {
+    mul_div(a, b, denominator, Rounding::Down)
+}
+
+/// `a * b / denominator`, rounded up.
+pub fn mul_div_up(a: U256, b: U256, denominator: U256) -> Result<U256, MathError> {
+    mul_div(a, b, denominator, Rounding::Up)
+}
+
+/// `a / b` with explicit rounding.
+pub fn div(a: U256, b: U256, rounding: Rounding) -> Result<U256, MathError> {
+    mul_div(a, U256::from(1u64), b, rounding)
+}
+
+// ============ Fixed-point newtypes ============
+
+macro_rules! fixed_point {
+    ($(#[$meta:meta])* $name:ident, $one:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+        pub struct $name(U256);
+
+        impl $name {
+            /// 0.0
+            pub const ZERO: Self = Self(U256::ZERO);
+            /// 1.0
+            pub const ONE: Self = Self($one);
+
+            /// Wraps an already-scaled raw value.
+            pub const fn from_raw(raw: U256) -> Self {
+                Self(raw)
+            }
+
+            /// The underlying scaled value.
+            pub const fn raw(self) -> U256 {
+                self.0
+            }
+
+            /// Scales an integer (e.g. `3` becomes `3.0`).
+            pub fn from_int(value: U256) -> Result<Self, MathError> {
+                value.checked_mul($one).map(Self).ok_or(MathError::Overflow)
+            }
+
+            /// `numerator / denominator` as a fixed-point value.
+            pub fn from_ratio(
+                numerator: U256,
+                denominator: U256,
+                rounding: Rounding,
+            ) -> Result<Self, MathError> {
+                mul_div(numerator, $one, denominator, rounding).map(Self)
+            }
+
+            /// Integer part, rounded in the given direction.
+            pub fn to_int(self, rounding: Rounding) -> U256 {
+                // The denominator is a non-zero constant and the result is <= self
+                div(self.0, $one, rounding).unwrap_or(U256::ZERO)
+            }
+
+            /// Fixed-point multiplication.
+            pub fn checked_mul(self, rhs: Self, rounding: Rounding) -> Result<Self, MathError> {
+                mul_div(self.0, rhs.0, $one, rounding).map(Self)
+            }
+
+            /// Fixed-point division.
+            pub fn checked_div(self, rhs: Self, rounding: Rounding) -> Result<Self, MathError> {
+                mul_div(self.0, $one, rhs.0, rounding).map(Self)
+            }
+
+            /// Addition that fails on overflow.
+            pub fn checked_add(self, rhs: Self) -> Result<Self, MathError> {
+                self.0.checked_add(rhs.0).map(Self).ok_or(MathError::Overflow)
+            }
+
+            /// Subtraction that fails on underflow.
+            pub fn checked_sub(self, rhs: Self) -> Result<Self, MathError> {
+                self.0.checked_sub(rhs.0).map(Self).ok_or(MathError::Overflow)
+            }
+        }
+    };
+}
+
+fixed_point!(
+    /// Unsigned fixed-point number with 18 decimals.
+    Wad,
+    WAD
+);
+
+fixed_point!(
+    /// Unsigned fixed-point number with 27 decimals.
+    Ray,
+    RAY
+);
+
+impl Wad {
+    /// Widens to RAY precision.
+    pub fn to_ray(self) -> Result<Ray, MathError> {
+        self.0
+            .checked_mul(WAD_TO_RAY)
+            .map(Ray)
+            .ok_or(MathError::Overflow)
+    }
+
+    /// Natural logarithm, WAD-scaled and signed.
+    pub fn ln(self) -> Result<I256, MathError> {
+        ln_wad(self.0)
+    }
+
+    /// `self ^ exponent` where the exponent is a signed WAD.
+    pub fn pow(self, exponent: I256) -> Result<Self, MathError> {
+        pow_wad(self.0, exponent).map(Self)
+    }
+}
+
+impl Ray {
+    /// Narrows to WAD precision.
+    pub fn to_wad(self, rounding: Rounding) -> Wad {
+        // The denominator is a non-zero constant and the result is <= self
+        Wad(div(self.0, WAD_TO_RAY, rounding).unwrap_or(U256::ZERO))
+    }
+}
+
+// ============ Transcendentals ============
+
+/// log2(x) for a WAD `x >= 1.0`, WAD-scaled.
+///
+/// Integer part from the most significant bit, then one fractional bit per
+/// squaring of the normalized mantissa.
+fn log2_wad_ge_one(x: U256) -> U256 {
+    let n = (x / WAD).bit_len() - 1;
+    let mut result = U256::from(n) * WAD;
+    // y is in [1.0, 2.0)
+    let mut y = x >> n;
+    if y == WAD {
+        return result;
+    }
+    let two: U256 = WAD << 1usize;
+    let mut delta: U256 = WAD >> 1usize;
+    while !delta.is_zero() {
+        // y < 2.0 so y * y < 4e36 cannot overflow
+        y = y * y / WAD;
+        if y >= two {
+            result += delta;
+            y >>= 1;
+        }
+        delta >>= 1;
+    }
+    result
+}
+
+/// Signed WAD from a magnitude that is known to fit (log results are < 2^9 * WAD).
+fn signed(magnitude: U256, negative: bool) -> I256 {
+    let value = I256::from_raw(magnitude);
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// log2(x) for a WAD `x`, WAD-scaled and signed.
+pub fn log2_wad(x: U256) -> Result<I256, MathError> {
+    if x.is_zero() {
+        return Err(MathError::Undefined);
+    }
+    if x >= WAD {
+        Ok(signed(log2_wad_ge_one(x), false))
+    } else {
+        // log2(x) = -log2(1 / x); x < 1.0 so 1e36 / x fits comfortably
+        let inverse = WAD * WAD / x;
+        Ok(signed(log2_wad_ge_one(inverse), true))
+    }
+}
+
+/// Natural logarithm of a WAD `x`, WAD-scaled and signed.
+pub fn ln_wad(x: U256) -> Result<I256, MathError> {
+    let log2 = log2_wad(x)?;
+    let magnitude = mul_div_down(log2.unsigned_abs(), WAD, U256::from(LOG2_E_WAD))?;
+    Ok(signed(magnitude, log2.is_negative()))
+}
+
+/// e^x for a signed WAD `x`, WAD-scaled.
+///
+/// Range-reduces to `x = k * ln2 + r` with `|r| <= ln2 / 2`, evaluates the
+/// Taylor series for e^r at 36 decimals and shifts the result by `k`.
+pub fn exp_wad(x: I256) -> Result<U256, MathError> {
+    if x < I256::try_from(EXP_MIN_INPUT).unwrap() {
+        return Ok(U256::ZERO);
+    }
+    if x > I256::try_from(EXP_MAX_INPUT).unwrap() {
+        return Err(MathError::Overflow);
+    }
+
+    let e36 = I256::try_from(E36).unwrap();
+    let ln2 = I256::try_from(LN2_E36).unwrap();
+    // |x| <= 136e18 so x * 1e18 stays far below 2^255
+    let x36 = x * I256::try_from(WAD).unwrap();
+
+    // k = round(x / ln2)
+    let half = ln2.asr(1);
+    let k = if x36.is_negative() {
+        (x36 - half) / ln2
+    } else {
+        (x36 + half) / ln2
+    };
+    let r = x36 - k * ln2;
+
+    let mut term = e36;
+    let mut sum = e36;
+    let mut n = 1u64;
+    while !term.is_zero() {
+        term = term * r / e36 / I256::try_from(n).unwrap();
+        sum += term;
+        n += 1;
+    }
+
+    // e^r is in (0.7, 1.42) so the sum is positive
+    let e_r = sum.into_raw() / WAD;
+    let k = k.as_i64();
+    if k >= 0 {
+        let shift = k as usize;
+        if e_r.bit_len() + shift > 256 {
+            return Err(MathError::Overflow);
+        }
+        Ok(e_r << shift)
+    } else {
+        Ok(e_r >> (k.unsigned_abs() as usize))
+    }
+}
+
+/// `x ^ y` for a WAD base and a signed WAD exponent, computed as e^(y * ln x).
+pub fn pow_wad(x: U256, y: I256) -> Result<U256, MathError> {
+    if y.is_zero() {
+        return Ok(WAD);
+    }
+    if x.is_zero() {
+        return if y.is_negative() {
+            Err(MathError::Undefined)
+        } else {
+            Ok(U256::ZERO)
+        };
+    }
+    let ln_x = ln_wad(x)?;
+    let negative = ln_x.is_negative() != y.is_negative();
+    let exponent = match mul_div_down(ln_x.unsigned_abs(), y.unsigned_abs(), WAD) {
+        Ok(magnitude) if magnitude < U256::from(EXP_MAX_INPUT as u128) => {
+            signed(magnitude, negative)
+        }
+        // Far outside exp's domain: saturate to zero or report overflow
+        _ if negative => return Ok(U256::ZERO),
+        _ => return Err(MathError::Overflow),
+    };
+    exp_wad(exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wad(x: u64) -> U256 {
+        U256::from(x) * WAD
+    }
+
+    fn iwad(x: i128) -> I256 {
+        I256::try_from(x).unwrap()
+    }
+
+    fn assert_close(actual: U256, expected: U256, tolerance: u64) {
+        let diff = if actual > expected {
+            actual - expected
+        } else {
+            expected - actual
+        };
+        assert!(
+            diff <= U256::from(tolerance),
+            "{actual} != {expected} (+/- {tolerance})"
+        );
+    }
+
+    fn assert_close_signed(actual: I256, expected: I256, tolerance: u64) {
+        let diff = (actual - expected).unsigned_abs();
+        assert!(
+            diff <= U256::from(tolerance),
+            "{actual} != {expected} (+/- {tolerance})"
+        );
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(WAD, U256::from(10u64).pow(U256::from(18u64)));
+        assert_eq!(RAY, U256::from(10u64).pow(U256::from(27u64)));
+    }
+
+    #[test]
+    fn test_mul_div_basic() {
+        assert_eq!(mul_div_down(wad(3), wad(2), WAD).unwrap(), wad(6));
+        assert_eq!(
+            mul_div_down(U256::from(10u64), U256::from(1u64), U256::from(3u64)).unwrap(),
+            U256::from(3u64)
+        );
+        assert_eq!(
+            mul_div_up(U256::from(10u64), U256::from(1u64), U256::from(3u64)).unwrap(),
+            U256::from(4u64)
+        );
+        // Exact division rounds the same either way
+        assert_eq!(
+            mul_div_up(U256::from(9u64), U256::from(1u64), U256::from(3u64)).unwrap(),
+            U256::from(3u64)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_wide_intermediate() {
+        // a * b overflows 256 bits but the result fits
+        assert_eq!(
+            mul_div_down(U256::MAX, U256::MAX, U256::MAX).unwrap(),
+            U256::MAX
+        );
+        assert_eq!(
+            mul_div_down(U256::MAX, wad(2), wad(4)).unwrap(),
+            U256::MAX / U256::from(2u64)
+        );
+    }
+
+    #[test]
+    fn test_mul_div_errors() {
+        assert_eq!(
+            mul_div_down(U256::MAX, U256::from(2u64), U256::from(1u64)),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            mul_div_down(WAD, WAD, U256::ZERO),
+            Err(MathError::DivisionByZero)
+        );
+        // Rounding up past U256::MAX overflows
+        assert_eq!(
+            mul_div_up(U256::MAX, U256::MAX, U256::MAX - U256::from(1u64)),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_wad_ops() {
+        let half = Wad::from_ratio(U256::from(1u64), U256::from(2u64), Rounding::Down).unwrap();
+        assert_eq!(half.raw(), WAD / U256::from(2u64));
+        let three = Wad::from_int(U256::from(3u64)).unwrap();
+        assert_eq!(
+            three.checked_mul(half, Rounding::Down).unwrap().raw(),
+            wad(3) / U256::from(2u64)
+        );
+        assert_eq!(
+            Wad::ONE.checked_div(three, Rounding::Down).unwrap().raw(),
+            U256::from(333_333_333_333_333_333u64)
+        );
+        assert_eq!(
+            Wad::ONE.checked_div(three, Rounding::Up).unwrap().raw(),
+            U256::from(333_333_333_333_333_334u64)
+        );
+        assert_eq!(
+            Wad::ONE.checked_div(Wad::ZERO, Rounding::Down),
+            Err(MathError::DivisionByZero)
+        );
+        assert_eq!(three.to_int(Rounding::Down), U256::from(3u64));
+        assert_eq!(half.to_int(Rounding::Up), U256::from(1u64));
+        assert_eq!(Wad::ZERO.checked_sub(Wad::ONE), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn test_wad_ray_conversion() {
+        let third = Wad::from_ratio(U256::from(1u64), U256::from(3u64), Rounding::Down).unwrap();
+        let ray = third.to_ray().unwrap();
+        assert_eq!(ray.raw(), third.raw() * U256::from(1_000_000_000u64));
+        assert_eq!(ray.to_wad(Rounding::Down), third);
+        let precise = Ray::from_ratio(U256::from(1u64), U256::from(3u64), Rounding::Down).unwrap();
+        assert_eq!(precise.to_wad(Rounding::Down), third);
+        assert_eq!(
+            precise.to_wad(Rounding::Up).raw(),
+            third.raw() + U256::from(1u64)
+        );
+    }
+
+    #[test]
+    fn test_ln() {
+        assert_eq!(ln_wad(WAD).unwrap(), I256::ZERO);
+        assert_close_signed(
+            ln_wad(U256::from(2_718_281_828_459_045_235u64)).unwrap(),
+            iwad(1_000_000_000_000_000_000),
+            10,
+        );
+        assert_close_signed(ln_wad(wad(2)).unwrap(), iwad(693_147_180_559_945_309), 10);
+        assert_close_signed(
+            ln_wad(WAD / U256::from(2u64)).unwrap(),
+            iwad(-693_147_180_559_945_309),
+            10,
+        );
+        // ln(1e18) = 41.446531673892822312
+        assert_close_signed(
+            ln_wad(wad(1_000_000_000_000_000_000)).unwrap(),
+            iwad(41_446_531_673_892_822_312),
+            100,
+        );
+        assert_eq!(ln_wad(U256::ZERO), Err(MathError::Undefined));
+    }
+
+    #[test]
+    fn test_exp() {
+        assert_eq!(exp_wad(I256::ZERO).unwrap(), WAD);
+        assert_close(
+            exp_wad(iwad(1_000_000_000_000_000_000)).unwrap(),
+            U256::from(2_718_281_828_459_045_235u64),
+            2,
+        );
+        assert_close(
+            exp_wad(iwad(-1_000_000_000_000_000_000)).unwrap(),
+            U256::from(367_879_441_171_442_321u64),
+            2,
+        );
+        // e^10 = 22026.465794806716516957
+        assert_close(
+            exp_wad(iwad(10_000_000_000_000_000_000)).unwrap(),
+            U256::from(22_026_465_794_806_716_516_957u128),
+            100_000,
+        );
+        assert!(exp_wad(iwad(135_000_000_000_000_000_000)).is_ok());
+        assert_eq!(
+            exp_wad(iwad(137_000_000_000_000_000_000)),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            exp_wad(iwad(-42_000_000_000_000_000_000)).unwrap(),
+            U256::ZERO
+        );
+    }
+
+    #[test]
+    fn test_exp_ln_roundtrip() {
+        for x in [1u64, 7, 1_000, 123_456_789] {
+            let roundtrip = exp_wad(ln_wad(wad(x)).unwrap()).unwrap();
+            // Relative error well below 1e-15
+            assert_close(roundtrip, wad(x), x * 1_000);
+        }
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_close(
+            pow_wad(wad(2), iwad(3_000_000_000_000_000_000)).unwrap(),
+            wad(8),
+            100,
+        );
+        assert_close(
+            pow_wad(wad(4), iwad(500_000_000_000_000_000)).unwrap(),
+            wad(2),
+            100,
+        );
+        assert_close(
+            pow_wad(wad(4), iwad(-500_000_000_000_000_000)).unwrap(),
+            WAD / U256::from(2u64),
+            100,
+        );
+        assert_eq!(pow_wad(wad(5), I256::ZERO).unwrap(), WAD);
+        assert_eq!(
+            pow_wad(U256::ZERO, iwad(1_000_000_000_000_000_000)).unwrap(),
+            U256::ZERO
+        );
+        assert_eq!(
+            pow_wad(U256::ZERO, iwad(-1_000_000_000_000_000_000)),
+            Err(MathError::Undefined)
+        );
+        assert_eq!(
+            pow_wad(wad(10), iwad(1_000_000_000_000_000_000_000)),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(
+            pow_wad(wad(10), iwad(-1_000_000_000_000_000_000_000)).unwrap(),
+            U256::ZERO
+        );
+        assert_close(
+            Wad::from_raw(wad(9))
+                .pow(iwad(500_000_000_000_000_000))
+                .unwrap()
+                .raw(),
+            wad(3),
+            100,
+        );
+    }
+}
@@ -0,0 +1,444 @@
+//! Uniswap V3/V4 tick and liquidity math, ported from `TickMath`,
+//! `SqrtPriceMath`, `LiquidityAmounts` and `Tick.getFeeGrowthInside`.
+//!
+//! Prices are Q64.96 square roots (`sqrtPriceX96`), liquidity is `uint128`,
+//! and token amounts round down as they do when valuing a position.
+
+use alloy_primitives::{I256, U256};
+
+use crate::{mul_div_down, MathError};
+
+/// Lowest tick whose price fits in a Q64.96 square root
+pub const MIN_TICK: i32 = -887_272;
+
+/// Highest tick whose price fits in a Q64.96 square root
+pub const MAX_TICK: i32 = -MIN_TICK;
+
+/// `sqrt_ratio_at_tick(MIN_TICK)`
+pub const MIN_SQRT_RATIO: U256 = U256::from_limbs([4_295_128_739, 0, 0, 0]);
+
+/// `sqrt_ratio_at_tick(MAX_TICK)`
+pub const MAX_SQRT_RATIO: U256 =
+    U256::from_limbs([0x5d95_1d52_6398_8d26, 0xefd1_fc6a_5064_8849, 0xfffd_8963, 0]);
+
+/// 2^96, the Q64.96 fixed-point one
+pub const Q96: U256 = U256::from_limbs([0, 0x1_0000_0000, 0, 0]);
+
+/// sqrt(1.0001)^-(2^i) in Q128.128 for i = 1..19, applied for each set bit of |tick|
+const TICK_RATIOS: [u128; 19] = [
+    0xfff9_7272_373d_4132_59a4_6990_580e_213a,
+    0xfff2_e50f_5f65_6932_ef12_357c_f3c7_fdcc,
+    0xffe5_caca_7e10_e4e6_1c36_24ea_a094_1cd0,
+    0xffcb_9843_d60f_6159_c9db_5883_5c92_6644,
+    0xff97_3b41_fa98_c081_472e_6896_dfb2_54c0,
+    0xff2e_a164_66c9_6a38_43ec_78b3_26b5_2861,
+    0xfe5d_ee04_6a99_a2a8_11c4_61f1_969c_3053,
+    0xfcbe_86c7_900a_88ae_dcff_c83b_479a_a3a4,
+    0xf987_a725_3ac4_1317_6f2b_074c_f781_5e54,
+    0xf339_2b08_22b7_0005_940c_7a39_8e4b_70f3,
+    0xe715_9475_a2c2_9b74_43b2_9c7f_a6e8_89d9,
+    0xd097_f3bd_fd20_22b8_845a_d8f7_92aa_5825,
+    0xa9f7_4646_2d87_0fdf_8a65_dc1f_90e0_61e5,
+    0x70d8_69a1_56d2_a1b8_90bb_3df6_2baf_32f7,
+    0x31be_135f_97d0_8fd9_8123_1505_542f_cfa6,
+    0x09aa_508b_5b7a_84e1_c677_de54_f3e9_9bc9,
+    0x005d_6af8_dedb_8119_6699_c329_225e_e604,
+    0x0000_2216_e584_f5fa_1ea9_2604_1bed_fe98,
+    0x0000_0000_048a_1703_91f7_dc42_444e_8fa2,
+];
+
+/// sqrt(1.0001)^-1 in Q128.128, used when bit 0 of |tick| is set
+const TICK_RATIO_ODD: u128 = 0xfffc_b933_bd6f_ad37_aa2d_162d_1a59_4001;
+
+/// sqrt(1.0001^tick) as a Q64.96, rounded up exactly as `TickMath.getSqrtRatioAtTick`.
+pub fn sqrt_ratio_at_tick(tick: i32) -> Result<U256, MathError> {
+    let abs_tick = tick.unsigned_abs();
+    if abs_tick > MAX_TICK as u32 {
+        return Err(MathError::TickOutOfRange);
+    }
+
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(TICK_RATIO_ODD)
+    } else {
+        U256::from(1u64) << 128
+    };
+    for (bit, &factor) in TICK_RATIOS.iter().enumerate() {
+        if abs_tick & (2 << bit) != 0 {
+            // ratio < 2^129 and factor < 2^128, so the product fits
+            ratio = (ratio * U256::from(factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    // Q128.128 to Q64.96, rounding up so the result is never below the true price
+    let round_up = !(ratio & U256::from(u32::MAX)).is_zero();
+    Ok((ratio >> 32) + U256::from(round_up as u64))
+}
+
+/// log_sqrt(1.0001)(2) in Q128: converts a Q64.64 log2 into a Q128.128 tick
+const LOG_SQRT10001_OF_2: u128 = 255_738_958_999_603_826_347_141;
+
+/// Error bounds of the log approximation in Q128.128 ticks, from `TickMath`
+const TICK_LOW_ERROR: u128 = 3_402_992_956_809_132_418_596_140_100_660_247_210;
+const TICK_HIGH_ERROR: u128 = 291_339_464_771_989_622_907_027_621_153_398_088_495;
+
+/// Greatest tick whose sqrt ratio is at most `sqrt_price_x96`, exactly as
+/// `TickMath.getTickAtSqrtRatio`. Accepts [MIN_SQRT_RATIO, MAX_SQRT_RATIO).
+pub fn tick_at_sqrt_ratio(sqrt_price_x96: U256) -> Result<i32, MathError> {
+    if sqrt_price_x96 < MIN_SQRT_RATIO || sqrt_price_x96 >= MAX_SQRT_RATIO {
+        return Err(MathError::TickOutOfRange);
+    }
+
+    // Q128.128 ratio; below 2^192 since sqrt_price < 2^160
+    let ratio: U256 = sqrt_price_x96 << 32usize;
+    let msb = ratio.bit_len() - 1;
+    let mut r: U256 = if msb >= 128 { ratio >> (msb - 127) } else { ratio << (127 - msb) };
+
+    // log2(ratio) in Q64.64: integer part from the msb, then 14 fractional bits by squaring
+    let mut log_2 = I256::try_from(msb as i64 - 128).unwrap() << 64usize;
+    for bit in (50..64usize).rev() {
+        r = (r * r) >> 127usize;
+        let f: U256 = r >> 128usize;
+        log_2 |= I256::from_raw(f << bit);
+        r >>= f.to::<usize>();
+    }
+
+    let log_sqrt10001 = log_2 * I256::from_raw(U256::from(LOG_SQRT10001_OF_2));
+    let tick_low = (log_sqrt10001 - I256::from_raw(U256::from(TICK_LOW_ERROR))).asr(128);
+    let tick_high = (log_sqrt10001 + I256::from_raw(U256::from(TICK_HIGH_ERROR))).asr(128);
+    // Both lie within [MIN_TICK - 1, MAX_TICK] for in-range prices
+    let tick_low = i32::try_from(tick_low).unwrap();
+    let tick_high = i32::try_from(tick_high).unwrap();
+
+    if tick_low == tick_high || sqrt_ratio_at_tick(tick_high)? > sqrt_price_x96 {
+        Ok(tick_low)
+    } else {
+        Ok(tick_high)
+    }
+}
+
+/// Fee growth per unit of liquidity inside [tick_lower, tick_upper) for one token,
+/// as `Tick.getFeeGrowthInside`. All values are Q128.128 accumulators that are meant
+/// to overflow, so every subtraction wraps modulo 2^256 and only differences of the
+/// result are meaningful.
+pub fn fee_growth_inside(
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_current: i32,
+    fee_growth_global_x128: U256,
+    fee_growth_outside_lower_x128: U256,
+    fee_growth_outside_upper_x128: U256,
+) -> U256 {
+    let below = if tick_current >= tick_lower {
+        fee_growth_outside_lower_x128
+    } else {
+        fee_growth_global_x128.wrapping_sub(fee_growth_outside_lower_x128)
+    };
+    let above = if tick_current < tick_upper {
+        fee_growth_outside_upper_x128
+    } else {
+        fee_growth_global_x128.wrapping_sub(fee_growth_outside_upper_x128)
+    };
+    fee_growth_global_x128.wrapping_sub(below).wrapping_sub(above)
+}
+
+/// Fees owed to `liquidity` between two fee-growth-inside snapshots of one token:
+/// `(end - start) * L / 2^128`, rounded down, as `Position.update`. The accumulator
+/// wraps by design, so `end - start` is taken modulo 2^256.
+pub fn fees_owed(
+    fee_growth_inside_start_x128: U256,
+    fee_growth_inside_end_x128: U256,
+    liquidity: u128,
+) -> Result<U256, MathError> {
+    let growth = fee_growth_inside_end_x128.wrapping_sub(fee_growth_inside_start_x128);
+    mul_div_down(growth, U256::from(liquidity), U256::from(1u64) << 128)
+}
+
+/// Sort two sqrt prices into (lower, upper).
+fn sorted(sqrt_a: U256, sqrt_b: U256) -> (U256, U256) {
+    if sqrt_a > sqrt_b {
+        (sqrt_b, sqrt_a)
+    } else {
+        (sqrt_a, sqrt_b)
+    }
+}
+
+/// Token0 held by `liquidity` between two sqrt prices:
+/// `L * 2^96 * (sqrtB - sqrtA) / sqrtB / sqrtA`, rounded down.
+pub fn amount0_for_liquidity(sqrt_a: U256, sqrt_b: U256, liquidity: u128) -> Result<U256, MathError> {
+    let (lower, upper) = sorted(sqrt_a, sqrt_b);
+    if lower.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    // L < 2^128, so L << 96 fits
+    let numerator = U256::from(liquidity) << 96;
+    Ok(mul_div_down(numerator, upper - lower, upper)? / lower)
+}
+
+/// Token1 held by `liquidity` between two sqrt prices: `L * (sqrtB - sqrtA) / 2^96`, rounded down.
+pub fn amount1_for_liquidity(sqrt_a: U256, sqrt_b: U256, liquidity: u128) -> Result<U256, MathError> {
+    let (lower, upper) = sorted(sqrt_a, sqrt_b);
+    mul_div_down(U256::from(liquidity), upper - lower, Q96)
+}
+
+/// (amount0, amount1) held by `liquidity` in the range [sqrt_a, sqrt_b] at `sqrt_price`,
+/// matching `LiquidityAmounts.getAmountsForLiquidity`.
+pub fn amounts_for_liquidity(
+    sqrt_price: U256,
+    sqrt_a: U256,
+    sqrt_b: U256,
+    liquidity: u128,
+) -> Result<(U256, U256), MathError> {
+    let (lower, upper) = sorted(sqrt_a, sqrt_b);
+    if sqrt_price <= lower {
+        Ok((amount0_for_liquidity(lower, upper, liquidity)?, U256::ZERO))
+    } else if sqrt_price < upper {
+        Ok((
+            amount0_for_liquidity(sqrt_price, upper, liquidity)?,
+            amount1_for_liquidity(lower, sqrt_price, liquidity)?,
+        ))
+    } else {
+        Ok((U256::ZERO, amount1_for_liquidity(lower, upper, liquidity)?))
+    }
+}
+
+/// Value `amount0` of token0 in token1 at `sqrt_price`: `amount0 * sqrtP^2 / 2^192`, rounded down.
+/// Squares the price directly when it fits, as `OracleLibrary.getQuoteAtTick` does.
+pub fn quote_token0_in_token1(amount0: U256, sqrt_price: U256) -> Result<U256, MathError> {
+    if sqrt_price <= U256::from(u128::MAX) {
+        mul_div_down(amount0, sqrt_price * sqrt_price, U256::from(1u64) << 192)
+    } else {
+        let price_x128 = mul_div_down(sqrt_price, sqrt_price, U256::from(1u64) << 64)?;
+        mul_div_down(amount0, price_x128, U256::from(1u64) << 128)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_ratio_at_tick_bounds() {
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK).unwrap(), MIN_SQRT_RATIO);
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK).unwrap(), MAX_SQRT_RATIO);
+        assert_eq!(
+            MAX_SQRT_RATIO.to_string(),
+            "1461446703485210103287273052203988822378723970342"
+        );
+        assert_eq!(sqrt_ratio_at_tick(MIN_TICK - 1), Err(MathError::TickOutOfRange));
+        assert_eq!(sqrt_ratio_at_tick(MAX_TICK + 1), Err(MathError::TickOutOfRange));
+    }
+
+    #[test]
+    fn test_sqrt_ratio_at_tick_known_values() {
+        assert_eq!(sqrt_ratio_at_tick(0).unwrap(), Q96);
+        assert_eq!(
+            sqrt_ratio_at_tick(1).unwrap().to_string(),
+            "79232123823359799118286999568"
+        );
+        assert_eq!(
+            sqrt_ratio_at_tick(-1).unwrap().to_string(),
+            "79224201403219477170569942574"
+        );
+    }
+
+    #[test]
+    fn test_sqrt_ratio_at_tick_v3_vectors() {
+        // Snapshot values from the Uniswap V3 TickMath tests
+        let vectors: [(i32, &str); 16] = [
+            (MIN_TICK + 1, "4295343490"),
+            (50, "79426470787362580746886972461"),
+            (100, "79625275426524748796330556128"),
+            (250, "80224679980005306637834519095"),
+            (500, "81233731461783161732293370115"),
+            (1_000, "83290069058676223003182343270"),
+            (2_500, "89776708723587163891445672585"),
+            (3_000, "92049301871182272007977902845"),
+            (4_000, "96768528593268422080558758223"),
+            (5_000, "101729702841318637793976746270"),
+            (50_000, "965075977353221155028623082916"),
+            (150_000, "143194173941309278083010301478497"),
+            (250_000, "21246587762933397357449903968194344"),
+            (500_000, "5697689776495288729098254600827762987878"),
+            (738_203, "847134979253254120489401328389043031315994541"),
+            (MAX_TICK - 1, "1461373636630004318706518188784493106690254656249"),
+        ];
+        for (tick, expected) in vectors {
+            assert_eq!(sqrt_ratio_at_tick(tick).unwrap().to_string(), expected, "tick {tick}");
+        }
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_ratio_bounds() {
+        assert_eq!(tick_at_sqrt_ratio(MIN_SQRT_RATIO).unwrap(), MIN_TICK);
+        assert_eq!(tick_at_sqrt_ratio(MIN_SQRT_RATIO + U256::from(1u64)).unwrap(), MIN_TICK);
+        assert_eq!(tick_at_sqrt_ratio(MAX_SQRT_RATIO - U256::from(1u64)).unwrap(), MAX_TICK - 1);
+        assert_eq!(tick_at_sqrt_ratio(Q96).unwrap(), 0);
+        assert_eq!(tick_at_sqrt_ratio(MIN_SQRT_RATIO - U256::from(1u64)), Err(MathError::TickOutOfRange));
+        assert_eq!(tick_at_sqrt_ratio(MAX_SQRT_RATIO), Err(MathError::TickOutOfRange));
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_ratio_inverts_sqrt_ratio_at_tick() {
+        for tick in [MIN_TICK, -500_000, -60_001, -200, -1, 0, 1, 887, 60_000, 423_111, MAX_TICK - 1] {
+            let sqrt_price = sqrt_ratio_at_tick(tick).unwrap();
+            // Exactly on a tick, and just below the next one
+            assert_eq!(tick_at_sqrt_ratio(sqrt_price).unwrap(), tick);
+            let next = sqrt_ratio_at_tick(tick + 1).unwrap();
+            assert_eq!(tick_at_sqrt_ratio(next - U256::from(1u64)).unwrap(), tick);
+        }
+        // encodePriceSqrt(1, 100) and (100, 1) from the V3 tests
+        let one_hundredth = U256::from(7_922_816_251_426_433_759_354_395_033u128);
+        assert_eq!(tick_at_sqrt_ratio(one_hundredth).unwrap(), -46_055);
+        assert_eq!(tick_at_sqrt_ratio(Q96 * U256::from(10u64)).unwrap(), 46_054);
+    }
+
+    #[test]
+    fn test_fee_growth_inside() {
+        let growth = |v: u64| U256::from(v) << 128;
+        // Current tick inside the range: global minus both outsides
+        let inside = fee_growth_inside(-60, 60, 0, growth(100), growth(15), growth(10));
+        assert_eq!(inside, growth(75));
+        // Below the range: everything inside accrued before the price left
+        let below = fee_growth_inside(-60, 60, -120, growth(100), growth(70), growth(10));
+        assert_eq!(below, growth(60));
+        // Above the range
+        let above = fee_growth_inside(-60, 60, 60, growth(100), growth(15), growth(60));
+        assert_eq!(above, growth(45));
+        // Outside values initialized above global wrap, as on-chain; differences still work
+        let start = fee_growth_inside(-60, 60, 0, growth(0), growth(5), growth(5));
+        let end = fee_growth_inside(-60, 60, 0, growth(30), growth(5), growth(5));
+        assert_eq!(start, U256::ZERO.wrapping_sub(growth(10)));
+        assert_eq!(end.wrapping_sub(start), growth(30));
+    }
+
+    #[test]
+    fn test_fees_owed() {
+        let q128 = U256::from(1u64) << 128;
+        // 3 units of fee per unit of liquidity
+        assert_eq!(fees_owed(q128, q128 * U256::from(4u64), 500).unwrap(), U256::from(1_500u64));
+        // Fractional growth rounds down
+        assert_eq!(fees_owed(U256::ZERO, q128 / U256::from(3u64), 10).unwrap(), U256::from(3u64));
+        assert_eq!(fees_owed(q128, q128, u128::MAX).unwrap(), U256::ZERO);
+        // Accumulator wrapped past 2^256 between snapshots
+        let start = U256::MAX - q128 + U256::from(1u64);
+        assert_eq!(fees_owed(start, q128, 7).unwrap(), U256::from(14u64));
+        // Largest possible growth with max liquidity still fits
+        assert!(fees_owed(U256::from(1u64), U256::ZERO, u128::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_amounts_for_liquidity_in_range() {
+        // Range [-60, 60] at price 1.0: symmetric amounts
+        let lower = sqrt_ratio_at_tick(-60).unwrap();
+        let upper = sqrt_ratio_at_tick(60).unwrap();
+        let (amount0, amount1) = amounts_for_liquidity(Q96, lower, upper, 1_000_000_000_000).unwrap();
+        let diff = if amount0 > amount1 { amount0 - amount1 } else { amount1 - amount0 };
+        assert!(diff <= U256::from(1u64));
+        assert_eq!(amount1, amount1_for_liquidity(lower, Q96, 1_000_000_000_000).unwrap());
+    }
+
+    #[test]
+    fn test_amounts_for_liquidity_out_of_range() {
+        let lower = sqrt_ratio_at_tick(100).unwrap();
+        let upper = sqrt_ratio_at_tick(200).unwrap();
+        let liquidity = 1_000_000_000_000_000_000u128;
+        // Below the range: all token0
+        let (amount0, amount1) = amounts_for_liquidity(Q96, lower, upper, liquidity).unwrap();
+        assert_eq!(amount0, amount0_for_liquidity(lower, upper, liquidity).unwrap());
+        assert_eq!(amount1, U256::ZERO);
+        // Above the range: all token1, argument order does not matter
+        let above = sqrt_ratio_at_tick(300).unwrap();
+        let (amount0, amount1) = amounts_for_liquidity(above, upper, lower, liquidity).unwrap();
+        assert_eq!(amount0, U256::ZERO);
+        assert_eq!(amount1, amount1_for_liquidity(lower, upper, liquidity).unwrap());
+    }
+
+    #[test]
+    fn test_amounts_for_liquidity_v3_vectors() {
+        // Snapshot values from the Uniswap V3 LiquidityAmounts tests:
+        // range [encodePriceSqrt(100, 110), encodePriceSqrt(110, 100)]
+        let sqrt = |v: &str| v.parse::<U256>().unwrap();
+        let lower = sqrt("75541088972021052633037516895");
+        let upper = sqrt("83095197869223157895945127772");
+        let vectors: [(U256, u128, u64, u64); 5] = [
+            // Price inside (encodePriceSqrt(1, 1))
+            (Q96, 2_148, 99, 99),
+            // Price below (encodePriceSqrt(99, 110)) and on the lower bound
+            (sqrt("75162434512514379355950439203"), 1_048, 99, 0),
+            (lower, 1_048, 99, 0),
+            // Price above (encodePriceSqrt(111, 100)) and on the upper bound
+            (sqrt("83472048772503575395047779114"), 2_097, 0, 199),
+            (upper, 2_097, 0, 199),
+        ];
+        for (sqrt_price, liquidity, amount0, amount1) in vectors {
+            assert_eq!(
+                amounts_for_liquidity(sqrt_price, lower, upper, liquidity).unwrap(),
+                (U256::from(amount0), U256::from(amount1)),
+                "sqrt price {sqrt_price}"
+            );
+        }
+
+        // SqrtPriceMath: 1e18 liquidity between prices 1 and 1.21 holds
+        // 0.0909... token0 or 0.1 token1, rounded down
+        let (one, one_point_one) = (Q96, sqrt("87150978765690771352898345369"));
+        let liquidity = 1_000_000_000_000_000_000u128;
+        assert_eq!(
+            amounts_for_liquidity(one, one, one_point_one, liquidity).unwrap(),
+            (U256::from(90_909_090_909_090_909u64), U256::ZERO)
+        );
+        assert_eq!(
+            amounts_for_liquidity(one_point_one, one, one_point_one, liquidity).unwrap(),
+            (U256::ZERO, U256::from(99_999_999_999_999_999u64))
+        );
+    }
+
+    #[test]
+    fn test_amounts_for_liquidity_tick_bounds() {
+        let liquidity = 1_000_000_000_000_000_000u128;
+        let amounts = |tick: i32, lower: i32, upper: i32| {
+            let sqrt_price = sqrt_ratio_at_tick(tick).unwrap();
+            let (amount0, amount1) = amounts_for_liquidity(
+                sqrt_price,
+                sqrt_ratio_at_tick(lower).unwrap(),
+                sqrt_ratio_at_tick(upper).unwrap(),
+                liquidity,
+            )
+            .unwrap();
+            (amount0.to_string(), amount1.to_string())
+        };
+        let pair = |amount0: &str, amount1: &str| (amount0.to_string(), amount1.to_string());
+
+        // [-60, 60] at ticks 0, -120 and 120
+        assert_eq!(amounts(0, -60, 60), pair("2995354955910780", "2995354955910780"));
+        assert_eq!(amounts(-120, -60, 60), pair("5999709018652706", "0"));
+        assert_eq!(amounts(120, -60, 60), pair("0", "5999709018652706"));
+
+        // Full range: price 1.0 holds L - 1 of each token, and each bound holds it all in one token
+        assert_eq!(amounts(0, MIN_TICK, MAX_TICK), pair("999999999999999999", "999999999999999999"));
+        assert_eq!(
+            amounts(MIN_TICK, MIN_TICK, MAX_TICK),
+            pair("18446050707367246063248664439159200717", "0")
+        );
+        assert_eq!(
+            amounts(MAX_TICK, MIN_TICK, MAX_TICK),
+            pair("0", "18446050711097703530314034002219689208")
+        );
+    }
+
+    #[test]
+    fn test_quote_token0_in_token1() {
+        // Price 1.0
+        assert_eq!(quote_token0_in_token1(U256::from(1_000u64), Q96).unwrap(), U256::from(1_000u64));
+        // sqrt price 2.0 => price 4.0
+        assert_eq!(
+            quote_token0_in_token1(U256::from(1_000u64), Q96 * U256::from(2u64)).unwrap(),
+            U256::from(4_000u64)
+        );
+        // Large sqrt price takes the two-step path: 2^60 sqrt => 2^120 price
+        let sqrt_price = Q96 << 60;
+        assert_eq!(quote_token0_in_token1(U256::from(3u64), sqrt_price).unwrap(), U256::from(3u64) << 120);
+    }
+}
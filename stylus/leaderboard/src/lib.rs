@@ -7,6 +7,7 @@ use stylus_sdk::prelude::*;
 #[cfg(any(target_arch = "wasm32", feature = "export-abi"))]
 use alloy_primitives::Address;
 use alloy_primitives::U256;
+use fixed_math::mul_div_down;
 
 // ============ ELO Constants ============
 
@@ -196,13 +197,13 @@ pub fn calculate_new_elo(winner_elo: U256, loser_elo: U256) -> (U256, U256) {
     // Expected score for winner using clamped linear approximation
     let expected_winner = if winner_elo >= loser_elo {
         let diff = winner_elo - loser_elo;
-        let bonus = (diff * scale) / spread4;
+        let bonus = scaled(diff, scale, spread4);
         let result = half_scale + bonus;
         // Clamp at scale (probability can't exceed 1.0)
         if result > scale { scale } else { result }
     } else {
         let diff = loser_elo - winner_elo;
-        let penalty = (diff * scale) / spread4;
+        let penalty = scaled(diff, scale, spread4);
         // Clamp at zero (probability can't go negative)
         if penalty >= half_scale {
             U256::ZERO
@@ -212,11 +213,11 @@ pub fn calculate_new_elo(winner_elo: U256, loser_elo: U256) -> (U256, U256) {
     };
 
     // Winner gain = K * (SCALE - expected) / SCALE  (since S=1 for winner)
-    let winner_gain = (k * (scale - expected_winner)) / scale;
+    let winner_gain = scaled(k, scale - expected_winner, scale);
 
     // Loser loss = K * expected_loser / SCALE  (since S=0 for loser)
     let expected_loser = scale - expected_winner;
-    let loser_loss = (k * expected_loser) / scale;
+    let loser_loss = scaled(k, expected_loser, scale);
 
     // Ensure winner always gains at least 1 ELO point
     let winner_gain = if winner_gain.is_zero() {
@@ -238,6 +239,13 @@ pub fn calculate_new_elo(winner_elo: U256, loser_elo: U256) -> (U256, U256) {
     (new_winner_elo, new_loser_elo)
 }
 
+/// `a * b / denominator` rounded down, with a 512-bit intermediate so large
+/// rating gaps cannot overflow. Every call has a non-zero constant denominator
+/// and a result no larger than `a` or `b`, so it cannot fail.
+fn scaled(a: U256, b: U256, denominator: U256) -> U256 {
+    mul_div_down(a, b, denominator).expect("Leaderboard: scaled ELO math failed")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -345,6 +353,19 @@ mod tests {
         assert!(new_l >= U256::from(100u64));
     }
 
+    #[test]
+    fn test_elo_extreme_gap_does_not_overflow() {
+        // (diff * SCALE) used to overflow for gaps near U256::MAX
+        let huge = U256::MAX / U256::from(2u64);
+        let (new_w, new_l) = calculate_new_elo(huge, U256::from(1000u64));
+        assert_eq!(new_w, huge + U256::from(1u64));
+        assert_eq!(new_l, U256::from(1000u64));
+
+        let (new_w, new_l) = calculate_new_elo(U256::from(1000u64), huge);
+        assert_eq!(new_w, U256::from(1032u64));
+        assert_eq!(new_l, huge - U256::from(32u64));
+    }
+
     #[test]
     fn test_elo_symmetric_outcomes() {
         let w = U256::from(1100u64);